@@ -0,0 +1,44 @@
+//! Times Monte Carlo coverage with and without the grid index.
+//! Run with `cargo run --release --example bench_index`.
+
+extern crate area_cov;
+extern crate time;
+
+use area_cov::models::{Configuration, Circle};
+use area_cov::models::index::GridIndex;
+use area_cov::models::sensing::*;
+use area_cov::ga::init::{random_init, random_points};
+
+fn main() {
+	let mut conf = Configuration::new();
+	conf.w = 100.; conf.h = 100.;
+	conf.n = 500;
+	conf.counts = vec![500];
+	conf.radius = vec![3.];
+	let state = random_init(&conf, 1).pop().unwrap();
+	let circles = Circle::from_state(&conf, &state);
+	let points = random_points(&conf, 200000);
+
+	let start = time::precise_time_ns();
+	let mut naive = 0;
+	for p in &points {
+		if is_covered(&conf, &circles, p) {
+			naive += 1;
+		}
+	}
+	let naive_ns = time::precise_time_ns() - start;
+
+	let start = time::precise_time_ns();
+	let index = GridIndex::new(&conf, &circles);
+	let mut indexed = 0;
+	for p in &points {
+		if detection_probability_among(&circles, index.candidates(p), p) >= conf.threshold {
+			indexed += 1;
+		}
+	}
+	let indexed_ns = time::precise_time_ns() - start;
+
+	println!("n=500, {} samples: naive {}ms, indexed {}ms, speedup {:.1}x", points.len(),
+		naive_ns / 1000000, indexed_ns / 1000000, naive_ns as f64 / indexed_ns as f64);
+	assert_eq!(naive, indexed);
+}
@@ -1,15 +1,29 @@
+use std::f32;
+use std::fs;
 use std::fs::File;
 use std::io;
 use std::io::BufRead;
 use std::io::prelude::*;
 use std::path::Path;
+use std::str::FromStr;
 
 use models::*;
 use models::point::Point;
+use models::sensing::SensingModel;
+use models::network::*;
+use models::adaptive::{coverage_area, Objective};
+use models::holes::coverage_holes;
+use ga::{Params, Checkpoint, Connectivity, Mode, Replacement};
+use ga::init::Initializer;
+use ga::mating::Mating;
+use ga::diversity::Diversity;
+use ga::nsga::Criterion;
+use ga::search::CountResult;
+use ga::redeploy::Plan;
+use json;
+use json::JsonObject;
 
 pub fn config_from_file(path: &Path) -> Configuration {
-	let mut c = Configuration::new();
-
 	let f = File::open(path)
 		.expect("File not found.");
 	let reader = io::BufReader::new(f); 
@@ -17,6 +31,12 @@ pub fn config_from_file(path: &Path) -> Configuration {
 		.map(|l| l.expect("Could not parse line"))
 		.collect();
 
+	config_from_lines(&lines)
+}
+
+pub fn config_from_lines(lines: &[String]) -> Configuration {
+	let mut c = Configuration::new();
+
 	// W H
 	let l = &lines[0];
 	let split : Vec<_> = l.split(" ").collect();
@@ -47,11 +67,85 @@ pub fn config_from_file(path: &Path) -> Configuration {
 			.expect("Failed to parse line");
 		c.radius.push(r);
 	}
+	c.sensing = vec![SensingModel::Binary; k as usize];
+	c.fov = vec![FULL_CIRCLE; k as usize];
+	c.comm = c.radius.iter().map(|r| 2. * r).collect();
+	c.cost = vec![1.; k as usize];
+
+	// Optional keyword lines
+	for l in &lines[2 + 2 * k as usize..] {
+		let split : Vec<_> = l.split_whitespace().collect();
+		if split.is_empty() {
+			continue;
+		}
+		parse_option(&mut c, &split);
+	}
 
 	sanity_check(&c);
 	c
 }
 
+fn parse_option(c: &mut Configuration, split: &[&str]) {
+	let num = |i: usize| split.get(i)
+		.expect("Missing value in option line")
+		.parse::<f32>()
+		.expect("Failed to parse line");
+	let type_index = |i: usize| {
+		let t = split.get(i)
+			.expect("Missing sensor type in option line")
+			.parse::<usize>()
+			.expect("Failed to parse line");
+		if t < 1 || t > c.counts.len() {
+			panic!("Sensor type {} does not exist. Types are numbered from 1.", t);
+		}
+		t - 1
+	};
+
+	match split[0] {
+		"threshold" => {
+			c.threshold = num(1);
+		},
+		"sensing" => {
+			let t = type_index(1);
+			let model = match split.get(2) {
+				Some(&"binary") => SensingModel::Binary,
+				Some(&"elfes") => SensingModel::Elfes {
+					re: num(3), lambda: num(4), beta: num(5)
+				},
+				_ => panic!("Unknown sensing model in line '{}'", split.join(" "))
+			};
+			c.sensing[t] = model;
+		},
+		"sector" => {
+			let t = type_index(1);
+			let fov = num(2);
+			if fov <= 0. || fov > 360. {
+				panic!("Field of view must be in (0, 360] degrees, got {}", fov);
+			}
+			c.fov[t] = fov.to_radians();
+		},
+		"comm" => {
+			let t = type_index(1);
+			c.comm[t] = num(2);
+		},
+		"fixed" => {
+			c.fixed.push(Circle::new(Point{x: num(1), y: num(2)}, num(3)));
+		},
+		"cost" => {
+			let t = type_index(1);
+			c.cost[t] = num(2);
+		},
+		"sink" => {
+			if split.get(1) == Some(&"free") {
+				c.free_sinks += 1;
+			} else {
+				c.sinks.push(Point{x: num(1), y: num(2)});
+			}
+		},
+		_ => panic!("Unknown option '{}'", split[0])
+	}
+}
+
 fn sanity_check(conf: &Configuration) {
 	let mut total_count = 0;
 	for c in &conf.counts {
@@ -65,18 +159,466 @@ fn sanity_check(conf: &Configuration) {
 
 pub fn result_to_file(conf: &Configuration, result: &Vec<Point>, cov: f32, path: &Path) -> Result<(), io::Error> {
 	let mut f = File::create(path).expect("Can't create file.");
+	let s = result_to_string(conf, result, cov);
+
+	f.write_all(s.as_bytes())
+}
+
+/// Formats a result. Sector sensors also list their heading and field of view in degrees.
+/// Sinks follow the sensors, fixed ones first.
+pub fn result_to_string(conf: &Configuration, result: &Vec<Point>, cov: f32) -> String {
 	let mut s = String::new();
 	let circles = Circle::from_state(conf, result);
 
+	// Type of each placed sensor, numbered from 1 as in the input file
+	let mut types: Vec<usize> = Vec::new();
+	for (t, count) in conf.counts.iter().enumerate() {
+		types.extend(vec![t + 1; *count as usize]);
+	}
+
 	s += &format!("{} {}\n", conf.w, conf.h);
 	s += &format!("{}\n", cov);
-	for c in circles {
-		s += &format!("{} {} {}\n", c.center.x, c.center.y, c.radius);
+	for (i, c) in circles.iter().enumerate() {
+		if c.is_sector() {
+			s += &format!("{} {} {} {} {} type {}\n", c.center.x, c.center.y, c.radius,
+				c.heading.to_degrees(), c.fov.to_degrees(), types[i]);
+		} else if i >= conf.n as usize {
+			s += &format!("{} {} {} fixed\n", c.center.x, c.center.y, c.radius);
+		} else {
+			s += &format!("{} {} {} type {}\n", c.center.x, c.center.y, c.radius, types[i]);
+		}
 	}
+	for p in conf.sink_positions(result) {
+		s += &format!("sink {} {}\n", p.x, p.y);
+	}
+
+	s
+}
+
+/// Writes a result with its network and routing tree as JSON
+pub fn result_to_json(conf: &Configuration, result: &Vec<Point>, cov: f32,
+		routing: Routing, battery: f32, path: &Path) -> Result<(), io::Error> {
+	let mut f = File::create(path).expect("Can't create file.");
+	let s = result_to_json_string(conf, result, cov, routing, battery);
 
 	f.write_all(s.as_bytes())
 }
 
+pub fn result_to_json_string(conf: &Configuration, result: &Vec<Point>, cov: f32,
+		routing: Routing, battery: f32) -> String {
+	let circles = Circle::from_state(conf, result);
+	let net = Network::from_state(conf, result);
+	let tree = RoutingTree::new(&net, routing);
+
+	let mut sensors: Vec<String> = Vec::new();
+	for (i, c) in circles.iter().enumerate() {
+		let mut o = JsonObject::new();
+		o.int("id", i as i64)
+			.num("x", c.center.x).num("y", c.center.y)
+			.num("radius", c.radius)
+			.boolean("fixed", i >= conf.n as usize);
+		if c.is_sector() {
+			o.num("heading", c.heading.to_degrees()).num("fov", c.fov.to_degrees());
+		}
+		sensors.push(o.build());
+	}
+
+	let mut sinks: Vec<String> = Vec::new();
+	for (i, p) in conf.sink_positions(result).iter().enumerate() {
+		let mut o = JsonObject::new();
+		o.int("id", i as i64)
+			.num("x", p.x).num("y", p.y)
+			.boolean("fixed", i < conf.sinks.len());
+		sinks.push(o.build());
+	}
+
+	// Parents are sensor ids, or null for sensors that talk to their sink directly
+	let mut routes: Vec<String> = Vec::new();
+	for i in 0..net.sensors {
+		let parent = match tree.parent[i] {
+			Some(p) if p < net.sensors => Some(p),
+			_ => None
+		};
+		let mut o = JsonObject::new();
+		o.int("sensor", i as i64)
+			.opt_int("sink", tree.sink[i])
+			.opt_int("parent", parent)
+			.opt_int("hops", tree.hops[i])
+			.num("length", tree.length[i])
+			.int("load", tree.load[i] as i64);
+		routes.push(o.build());
+	}
+
+	let mut routing_obj = JsonObject::new();
+	routing_obj.string("mode", match routing {
+			Routing::MinHop => "hops",
+			Routing::ShortestPath => "distance"
+		})
+		.int("max_load", tree.max_load() as i64)
+		.num("lifetime", tree.lifetime(battery))
+		.raw("routes", json::array(&routes));
+
+	let mut holes: Vec<String> = Vec::new();
+	for h in coverage_holes(conf, result) {
+		let mut o = JsonObject::new();
+		o.num("area", h.area)
+			.num("x0", h.min.x).num("y0", h.min.y)
+			.num("x1", h.max.x).num("y1", h.max.y)
+			.num("cx", h.centroid.x).num("cy", h.centroid.y)
+			.num("radius", h.radius);
+		holes.push(o.build());
+	}
+
+	let mut network = JsonObject::new();
+	network.int("components", net.component_count() as i64)
+		.int("diameter", net.diameter() as i64);
+
+	let mut o = JsonObject::new();
+	o.num("width", conf.w).num("height", conf.h)
+		.num("coverage", cov)
+		.raw("sensors", json::array(&sensors))
+		.raw("sinks", json::array(&sinks))
+		.raw("holes", json::array(&holes))
+		.raw("network", network.build())
+		.raw("routing", routing_obj.build());
+	o.build() + "\n"
+}
+
+/// Writes every layout of a Pareto front next to `path`, numbered from 0,
+/// along with a CSV summary of their objective values
+pub fn front_to_files(conf: &Configuration, front: &[Vec<Point>], scores: &[Vec<f32>],
+		criteria: &Vec<Criterion>, path: &Path) -> Result<(), io::Error> {
+	let stem = path.file_stem().unwrap().to_str().unwrap();
+	let ext = path.extension().map_or("out", |e| e.to_str().unwrap());
+
+	let mut csv = String::from("layout,file");
+	for c in criteria {
+		csv += &format!(",{}", c.name());
+	}
+	csv += "\n";
+
+	for i in 0..front.len() {
+		let name = format!("{}-{}.{}", stem, i, ext);
+		let cov = coverage_area(conf, &front[i]);
+		result_to_file(conf, &front[i], cov, &path.with_file_name(&name))?;
+
+		csv += &format!("{},{}", i, name);
+		for (k, c) in criteria.iter().enumerate() {
+			csv += &format!(",{}", c.measured(scores[i][k]));
+		}
+		csv += "\n";
+	}
+
+	let mut f = File::create(path.with_file_name(format!("{}-front.csv", stem)))
+		.expect("Can't create file.");
+	f.write_all(csv.as_bytes())
+}
+
+pub fn plan_to_file(plan: &Plan, path: &Path) -> Result<(), io::Error> {
+	let mut f = File::create(path).expect("Can't create file.");
+	f.write_all(plan_to_string(plan).as_bytes())
+}
+
+/// Formats a movement plan: the total and longest travel, then one line per sensor
+/// with its type, its index in the current layout, the index of its target spot,
+/// both positions and the distance
+pub fn plan_to_string(plan: &Plan) -> String {
+	let mut s = format!("{} {}\n", plan.total, plan.max);
+	for m in &plan.moves {
+		s += &format!("{} {} {} {} {} {} {} {}\n", m.sensor_type + 1, m.sensor, m.spot,
+			m.from.x, m.from.y, m.to.x, m.to.y, m.distance);
+	}
+	s
+}
+
+/// Writes the coverage reached for each sensor count of a minimum-sensor search as CSV
+pub fn curve_to_file(curve: &Vec<CountResult>, path: &Path) -> Result<(), io::Error> {
+	let mut csv = String::from("sensors,counts,coverage\n");
+	for r in curve {
+		let counts: Vec<String> = r.counts.iter().map(|c| c.to_string()).collect();
+		csv += &format!("{},{},{}\n", r.n, counts.join(" "), r.coverage);
+	}
+
+	let mut f = File::create(path).expect("Can't create file.");
+	f.write_all(csv.as_bytes())
+}
+
+/// Reads the state of a file written by `result_to_file`
+pub fn result_from_file(conf: &Configuration, path: &Path) -> Vec<Point> {
+	let f = File::open(path)
+		.expect("File not found.");
+	let reader = io::BufReader::new(f);
+	let lines : Vec<_> = reader.lines()
+		.map(|l| l.expect("Could not parse line"))
+		.collect();
+
+	result_from_lines(conf, &lines)
+}
+
+/// Reads the configuration matching a result file, see `result_config_from_lines`
+pub fn result_config_from_file(conf: &Configuration, path: &Path) -> Configuration {
+	let f = File::open(path)
+		.expect("File not found.");
+	let reader = io::BufReader::new(f);
+	let lines : Vec<_> = reader.lines()
+		.map(|l| l.expect("Could not parse line"))
+		.collect();
+
+	result_config_from_lines(conf, &lines)
+}
+
+/// Counts the sensors of each type listed in a result. Results of runs with
+/// variable counts may deploy fewer sensors than the input declares.
+pub fn result_config_from_lines(conf: &Configuration, lines: &[String]) -> Configuration {
+	let mut c = conf.clone();
+	c.counts = vec![0; conf.counts.len()];
+	for l in &lines[2..] {
+		let split : Vec<_> = l.split_whitespace().collect();
+		if split.is_empty() || split[0] == "sink" || split.last() == Some(&"fixed") {
+			continue;
+		}
+		let t = match split.iter().position(|v| *v == "type") {
+			Some(k) => split[k + 1].parse::<usize>().expect("Failed to parse line"),
+			None => panic!("Result line '{}' has no sensor type.", l)
+		};
+		if t == 0 || t > c.counts.len() {
+			panic!("Unknown sensor type {}.", t);
+		}
+		if c.counts[t - 1] == conf.counts[t - 1] {
+			panic!("Result lists more sensors of type {} than the configuration declares.", t);
+		}
+		c.counts[t - 1] += 1;
+	}
+	c.n = c.counts.iter().sum();
+	c
+}
+
+pub fn result_from_lines(conf: &Configuration, lines: &[String]) -> Vec<Point> {
+	let mut state: Vec<Point> = Vec::new();
+	let mut headings: Vec<Point> = Vec::new();
+	let mut sinks: Vec<Point> = Vec::new();
+	for l in &lines[2..] {
+		let split : Vec<_> = l.split_whitespace().collect();
+		if split.is_empty() {
+			continue;
+		}
+		// Fixed sensors come from the configuration
+		if split.last() == Some(&"fixed") {
+			continue;
+		}
+		if split[0] == "sink" {
+			let x = split[1].parse::<f32>().expect("Failed to parse line");
+			let y = split[2].parse::<f32>().expect("Failed to parse line");
+			sinks.push(Point{x, y});
+			continue;
+		}
+		let values: Vec<f32> = split.iter()
+			.take_while(|v| **v != "type")
+			.map(|v| v.parse::<f32>().expect("Failed to parse line"))
+			.collect();
+		state.push(Point{x: values[0], y: values[1]});
+		if values.len() >= 5 {
+			headings.push(Point::from_angle(values[3].to_radians()));
+		}
+	}
+
+	if state.len() != conf.n as usize {
+		panic!("Result has {} sensors but the configuration declares {}.", state.len(), conf.n);
+	}
+	if headings.len() != conf.sector_count() as usize {
+		panic!("Result has {} sector sensors but the configuration declares {}.",
+			headings.len(), conf.sector_count());
+	}
+	if sinks.len() < conf.free_sinks as usize {
+		panic!("Result has {} sinks but the configuration declares {} free sinks.",
+			sinks.len(), conf.free_sinks);
+	}
+	state.append(&mut headings);
+	// Free sinks are listed after the fixed ones
+	let free = sinks.len() - conf.free_sinks as usize;
+	state.extend_from_slice(&sinks[free..]);
+	state
+}
+
+/// Writes a GA checkpoint, see `checkpoint_to_string`
+pub fn checkpoint_to_file(run: &Checkpoint, path: &Path) -> Result<(), io::Error> {
+	// Write next to the old checkpoint first so a kill never leaves a partial file
+	let tmp = path.with_extension("tmp");
+	{
+		let mut f = File::create(&tmp)?;
+		f.write_all(checkpoint_to_string(run).as_bytes())?;
+	}
+	fs::rename(&tmp, path)
+}
+
+fn points_to_string(state: &[Point]) -> String {
+	let values: Vec<String> = state.iter().map(|p| format!("{} {}", p.x, p.y)).collect();
+	values.join(" ")
+}
+
+fn optional_to_string(v: Option<f32>) -> String {
+	match v {
+		Some(v) => v.to_string(),
+		None => String::from("-")
+	}
+}
+
+fn objective_to_string(o: &Objective) -> String {
+	match *o {
+		Objective::Overlap => String::from("overlap"),
+		Objective::LegacyOverlap => String::from("legacy-overlap"),
+		Objective::KCoverage(k) => format!("kcov {}", k),
+		Objective::Coverage => String::from("coverage"),
+		Objective::Hybrid { coverage_weight, overlap_weight } => {
+			format!("hybrid {} {}", coverage_weight, overlap_weight)
+		},
+		Objective::Robust { failure, trials } => format!("robust {} {}", failure, trials)
+	}
+}
+
+fn mode_to_string(m: &Mode) -> String {
+	match *m {
+		Mode::Generational => String::from("generational"),
+		Mode::SteadyState(Replacement::Worst) => String::from("steady-state worst"),
+		Mode::SteadyState(Replacement::Tournament(k)) => format!("steady-state tournament {}", k)
+	}
+}
+
+/// Formats a GA checkpoint: one `key value...` line per parameter, the number of
+/// completed generations, the RNG state, the best score and state, then one
+/// `state` line per member of the pool. Numbers are written so they read back exactly.
+pub fn checkpoint_to_string(run: &Checkpoint) -> String {
+	let p = &run.params;
+	let mut s = String::new();
+	s += &format!("generation {}\n", run.generation);
+	s += &format!("rng {} {}\n", run.rng[0], run.rng[1]);
+	s += &format!("size {}\n", p.size);
+	s += &format!("iters {}\n", p.iters);
+	s += &format!("cross_ratio {}\n", p.cross_ratio);
+	s += &format!("mutate_ratio {}\n", p.mutate_ratio);
+	s += &format!("objective {}\n", objective_to_string(&p.objective));
+	s += &format!("connectivity {}\n", p.connectivity.name());
+	s += &format!("require_sink {}\n", p.require_sink);
+	s += &format!("budget {}\n", optional_to_string(p.budget));
+	s += &format!("target {}\n", optional_to_string(p.target));
+	s += &format!("init {}\n", p.init.name());
+	s += &format!("mode {}\n", mode_to_string(&p.mode));
+	s += &format!("evaluations {}\n", p.evaluations.map_or(String::from("-"), |e| e.to_string()));
+	s += &format!("mating {}\n", p.mating.name());
+	s += &format!("offspring {}\n", p.offspring.map_or(String::from("-"), |o| o.to_string()));
+	s += &format!("diversity {}\n", p.diversity.name());
+	s += &format!("dedup {}\n", optional_to_string(p.dedup));
+	s += &format!("align {}\n", p.align);
+	s += &format!("realign_every {}\n", p.realign_every);
+	if !run.scores.is_empty() {
+		let scores: Vec<String> = run.scores.iter().map(|v| v.to_string()).collect();
+		s += &format!("scores {}\n", scores.join(" "));
+	}
+	s += &format!("best {} {}\n", run.best_score, points_to_string(&run.best_state));
+	for state in &run.pool {
+		s += &format!("state {}\n", points_to_string(state));
+	}
+	s
+}
+
+pub fn checkpoint_from_file(path: &Path) -> Checkpoint {
+	let f = File::open(path)
+		.expect("File not found.");
+	let reader = io::BufReader::new(f);
+	let lines : Vec<_> = reader.lines()
+		.map(|l| l.expect("Could not parse line"))
+		.collect();
+
+	checkpoint_from_lines(&lines)
+}
+
+fn parse<T: FromStr>(v: &str) -> T {
+	match v.parse::<T>() {
+		Ok(v) => v,
+		Err(_) => panic!("Failed to parse checkpoint value '{}'", v)
+	}
+}
+
+fn points_from_strs(values: &[&str]) -> Vec<Point> {
+	values.chunks(2)
+		.map(|c| Point{x: parse(c[0]), y: parse(c[1])})
+		.collect()
+}
+
+fn optional_from_str(v: &str) -> Option<f32> {
+	if v == "-" { None } else { Some(parse(v)) }
+}
+
+fn mode_from_strs(split: &[&str]) -> Mode {
+	match split {
+		["generational"] => Mode::Generational,
+		["steady-state", "worst"] => Mode::SteadyState(Replacement::Worst),
+		["steady-state", "tournament", k] => Mode::SteadyState(Replacement::Tournament(parse(k))),
+		_ => panic!("Unknown mode '{}' in checkpoint", split.join(" "))
+	}
+}
+
+fn objective_from_strs(split: &[&str]) -> Objective {
+	match split[0] {
+		"overlap" => Objective::Overlap,
+		"legacy-overlap" => Objective::LegacyOverlap,
+		"kcov" => Objective::KCoverage(parse(split[1])),
+		"coverage" => Objective::Coverage,
+		"hybrid" => Objective::Hybrid { coverage_weight: parse(split[1]), overlap_weight: parse(split[2]) },
+		"robust" => Objective::Robust { failure: parse(split[1]), trials: parse(split[2]) },
+		o => panic!("Unknown objective '{}' in checkpoint", o)
+	}
+}
+
+pub fn checkpoint_from_lines(lines: &[String]) -> Checkpoint {
+	let mut run = Checkpoint::new(&Params::new(), [0, 0]);
+	for l in lines {
+		let split : Vec<_> = l.split_whitespace().collect();
+		if split.is_empty() {
+			continue;
+		}
+		let p = &mut run.params;
+		match split[0] {
+			"generation" => run.generation = parse(split[1]),
+			"rng" => run.rng = [parse(split[1]), parse(split[2])],
+			"size" => p.size = parse(split[1]),
+			"iters" => p.iters = parse(split[1]),
+			"cross_ratio" => p.cross_ratio = parse(split[1]),
+			"mutate_ratio" => p.mutate_ratio = parse(split[1]),
+			"objective" => p.objective = objective_from_strs(&split[1..]),
+			"connectivity" => p.connectivity = Connectivity::from_name(split[1])
+				.expect("Unknown connectivity in checkpoint"),
+			"require_sink" => p.require_sink = parse(split[1]),
+			"budget" => p.budget = optional_from_str(split[1]),
+			"target" => p.target = optional_from_str(split[1]),
+			"init" => p.init = Initializer::from_name(split[1])
+				.expect("Unknown initializer in checkpoint"),
+			"mode" => p.mode = mode_from_strs(&split[1..]),
+			"evaluations" => p.evaluations = if split[1] == "-" { None } else { Some(parse(split[1])) },
+			"mating" => p.mating = Mating::from_name(split[1])
+				.expect("Unknown mating in checkpoint"),
+			"offspring" => p.offspring = if split[1] == "-" { None } else { Some(parse(split[1])) },
+			"diversity" => p.diversity = match split[1] {
+				"sharing" => Diversity::Sharing(parse(split[2])),
+				"crowding" => Diversity::Crowding,
+				"none" => Diversity::None,
+				d => panic!("Unknown diversity '{}' in checkpoint", d)
+			},
+			"dedup" => p.dedup = optional_from_str(split[1]),
+			"align" => p.align = parse(split[1]),
+			"realign_every" => p.realign_every = parse(split[1]),
+			"scores" => run.scores = split[1..].iter().map(|v| parse(v)).collect(),
+			"best" => {
+				run.best_score = parse(split[1]);
+				run.best_state = points_from_strs(&split[2..]);
+			},
+			"state" => run.pool.push(points_from_strs(&split[1..])),
+			k => panic!("Unknown checkpoint entry '{}'", k)
+		}
+	}
+	run
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -94,6 +636,114 @@ mod tests {
 		assert_eq!(cf.n, 17);
 		assert_eq!(cf.counts.len(), 3);
 		assert_eq!(cf.radius.len(), 3);
+		assert_eq!(cf.sensing, vec![SensingModel::Binary; 3]);
+	}
+
+	#[test]
+	fn test_options() {
+		let lines: Vec<String> = [
+			"100 100", "2 3", "1", "2", "10", "8",
+			"threshold 0.8",
+			"sensing 2 elfes 2 0.5 1",
+			"comm 1 25",
+			"sink 50 0",
+			"sink free"
+		].iter().map(|l| l.to_string()).collect();
+		let cf = config_from_lines(&lines);
+
+		assert_eq!(cf.comm, vec![25., 16.]);
+		assert_eq!(cf.sinks, vec![Point{x: 50., y: 0.}]);
+		assert_eq!(cf.free_sinks, 1);
+		assert_eq!(cf.threshold, 0.8);
+		assert_eq!(cf.sensing[0], SensingModel::Binary);
+		assert_eq!(cf.sensing[1], SensingModel::Elfes { re: 2., lambda: 0.5, beta: 1. });
+	}
+
+	#[test]
+	fn test_sector_result() {
+		let lines: Vec<String> = [
+			"100 100", "2 3", "2", "1", "10", "8",
+			"sector 1 90"
+		].iter().map(|l| l.to_string()).collect();
+		let mut cf = config_from_lines(&lines);
+		cf.sinks = vec![Point{x: 0., y: 0.}];
+		cf.free_sinks = 1;
+		assert!(cf.is_sector(0));
+		assert!(!cf.is_sector(1));
+		assert_eq!(cf.genome_len(), 6);
+
+		let result = init::random_init(&cf, 1).pop().unwrap();
+		let s = result_to_string(&cf, &result, 100.);
+		println!("{}", s);
+		let lines: Vec<String> = s.lines().map(|l| l.to_string()).collect();
+		let read = result_from_lines(&cf, &lines);
+
+		assert_eq!(read.len(), result.len());
+		for i in 0..read.len() {
+			assert!(read[i].distance(&result[i]) < 1e-3);
+		}
+	}
+
+	#[test]
+	fn test_fixed_result() {
+		let lines: Vec<String> = [
+			"100 100", "1 2", "2", "10",
+			"fixed 50 50 12"
+		].iter().map(|l| l.to_string()).collect();
+		let cf = config_from_lines(&lines);
+		assert_eq!(cf.fixed.len(), 1);
+		assert_eq!(cf.genome_len(), 2);
+
+		let result = init::random_init(&cf, 1).pop().unwrap();
+		let s = result_to_string(&cf, &result, 100.);
+		println!("{}", s);
+		assert!(s.ends_with("50 50 12 fixed\n"));
+		assert!(result_to_json_string(&cf, &result, 100., Routing::MinHop, 1.).contains("\"fixed\": true"));
+
+		let lines: Vec<String> = s.lines().map(|l| l.to_string()).collect();
+		assert_eq!(result_config_from_lines(&cf, &lines).counts, vec![2]);
+		let read = result_from_lines(&cf, &lines);
+		assert_eq!(read.len(), 2);
+	}
+
+	#[test]
+	fn test_result_config() {
+		let mut cf = Configuration::new();
+		cf.w = 100.; cf.h = 100.;
+		cf.n = 5;
+		cf.counts = vec![3, 2];
+		cf.radius = vec![10., 20.];
+		let lines: Vec<String> = ["100 100", "500", "1 1 10 type 1", "2 2 20 type 2", "sink 3 3"]
+			.iter().map(|l| l.to_string()).collect();
+
+		let c = result_config_from_lines(&cf, &lines);
+		assert_eq!(c.counts, vec![1, 1]);
+		assert_eq!(c.n, 2);
+		assert_eq!(result_from_lines(&c, &lines).len(), 2);
+
+		// Types with the same radius are told apart
+		cf.radius = vec![10., 10.];
+		let lines: Vec<String> = ["100 100", "500", "1 1 10 type 2", "2 2 10 type 2"]
+			.iter().map(|l| l.to_string()).collect();
+		assert_eq!(result_config_from_lines(&cf, &lines).counts, vec![0, 2]);
+	}
+
+	#[test]
+	fn test_json_result() {
+		let mut cf = Configuration::new();
+		cf.w = 100.; cf.h = 100.;
+		cf.n = 2;
+		cf.counts = vec![2];
+		cf.radius = vec![5.];
+		cf.sinks = vec![Point{x: 0., y: 0.}];
+		let result = vec![Point{x: 6., y: 0.}, Point{x: 14., y: 0.}];
+
+		let s = result_to_json_string(&cf, &result, 50., Routing::MinHop, 100.);
+		println!("{}", s);
+		assert!(s.contains("\"routes\": [{\"sensor\": 0, \"sink\": 0, \"parent\": null, \"hops\": 1, \"length\": 6, \"load\": 1}, \
+			{\"sensor\": 1, \"sink\": 0, \"parent\": 0, \"hops\": 2, \"length\": 14, \"load\": 0}]"));
+		assert!(s.contains("\"lifetime\": 50"));
+		assert!(s.contains("\"holes\": [{\"area\": "));
 	}
 
 	#[test]
@@ -1,16 +1,24 @@
 use std::f32;
 use rand::distributions::{IndependentSample, Range};
-use rand;
+use random;
 
 use models::*;
 use models::point::Point;
 use ga::hungarian::apply_hungarian;
 
+/// BLX-alpha crossover on every gene. Heading genes are blended as vectors, which avoids
+/// the discontinuity at +-PI; `init::normalize` projects them back onto the unit circle.
+/// Genes the parents agree on are copied unchanged, every other gene is sampled
+/// from the parents' range widened by `alpha` on each side.
 pub fn blx_alpha(p1: &Vec<Point>, p2: &Vec<Point>, alpha: f32) -> Vec<Point> {
 	let mut child: Vec<Point> = Vec::new();
-	let mut r = rand::thread_rng();
+	let mut r = random::thread_rng();
 	
 	for i in 0..p1.len() {
+		if p1[i] == p2[i] {
+			child.push(p1[i]);
+			continue;
+		}
 		let start_x = f32::min(p1[i].x, p2[i].x);
 		let end_x = f32::max(p1[i].x, p2[i].x) + 1e-3; // Prevent error when start_x == end_x
 		let start_y = f32::min(p1[i].y, p2[i].y);
@@ -29,21 +37,52 @@ pub fn blx_alpha(p1: &Vec<Point>, p2: &Vec<Point>, alpha: f32) -> Vec<Point> {
 
 /// Creates a new pair of parents from p1 and p2 that have the optimal per-type distance
 pub fn homogenize(conf: &Configuration, p1: &Vec<Point>, p2: &Vec<Point>) -> (Vec<Point>, Vec<Point>) {
-	let mut px2: Vec<Point> = Vec::new();
+	let mut px2: Vec<Point> = p2.clone();
 	let mut s: usize = 0;
-	let weight_fn = |p1: &Point, p2: &Point| -p1.distance(p2);
-	for c in &conf.counts {
-		let _c = *c as usize;
-		let mut g1 = p1[s..s+_c].to_vec();
-		let mut g2 = p2[s..s+_c].to_vec();
-		apply_hungarian::<Point>(&mut g1, &mut g2, &weight_fn);
-		px2.append(&mut g2);
+	let mut h = conf.n as usize;
+	let f = conf.flag_genes();
+	// Sensors are matched by position, heading and activation genes follow their sensor
+	let weight_fn = |p1: &(Point, Point, Point), p2: &(Point, Point, Point)| -p1.0.distance(&p2.0);
+	for t in 0..conf.counts.len() {
+		let _c = conf.counts[t] as usize;
+		if _c == 0 {
+			continue;
+		}
+		let sector = conf.is_sector(t);
+		let gene = |p: &Vec<Point>, i: usize| {
+			let heading = if sector { p[h + i] } else { Point::wrap(0.) };
+			let flag = if conf.variable_counts { p[f + s + i] } else { Point::wrap(0.) };
+			(p[s + i], heading, flag)
+		};
+		let mut g1: Vec<(Point, Point, Point)> = (0.._c).map(|i| gene(p1, i)).collect();
+		let mut g2: Vec<(Point, Point, Point)> = (0.._c).map(|i| gene(p2, i)).collect();
+		apply_hungarian::<(Point, Point, Point)>(&mut g1, &mut g2, &weight_fn);
+		for i in 0.._c {
+			px2[s + i] = g2[i].0;
+			if sector {
+				px2[h + i] = g2[i].1;
+			}
+			if conf.variable_counts {
+				px2[f + s + i] = g2[i].2;
+			}
+		}
 		s += _c;
+		if sector {
+			h += _c;
+		}
 	}
 
 	(p1.clone(), px2)
 }
 
+/// Reorders the sensors of every state to match `reference` as closely as possible
+pub fn align_to(conf: &Configuration, reference: &Vec<Point>, states: &mut [Vec<Point>]) {
+	for state in states.iter_mut() {
+		let (_, aligned) = homogenize(conf, reference, state);
+		*state = aligned;
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -82,5 +121,66 @@ mod tests {
 		println!("{:?}", p1);
 		println!("{:?}", p2);
 	}
+
+	#[test]
+	fn test_cross_permuted() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 5;
+		conf.counts = vec![2, 3];
+		conf.radius = vec![10., 20.];
+		conf.fov = vec![FULL_CIRCLE, 1.];
+		conf.free_sinks = 1;
+		conf.variable_counts = true;
+
+		let s1 = vec![
+			Point{x: 10., y: 10.}, Point{x: 40., y: 60.},
+			Point{x: 20., y: 80.}, Point{x: 80., y: 20.}, Point{x: 70., y: 70.},
+			Point{x: 1., y: 0.}, Point{x: 0., y: 1.}, Point{x: -1., y: 0.},
+			Point{x: 1., y: 0.}, Point{x: 0.2, y: 0.}, Point{x: 0.9, y: 0.}, Point{x: 0.1, y: 0.}, Point{x: 0.6, y: 0.},
+			Point{x: 50., y: 50.}
+		];
+		// The same layout with the sensors of each type listed in another order
+		let s2 = vec![
+			Point{x: 40., y: 60.}, Point{x: 10., y: 10.},
+			Point{x: 70., y: 70.}, Point{x: 20., y: 80.}, Point{x: 80., y: 20.},
+			Point{x: -1., y: 0.}, Point{x: 1., y: 0.}, Point{x: 0., y: 1.},
+			Point{x: 0.2, y: 0.}, Point{x: 1., y: 0.}, Point{x: 0.6, y: 0.}, Point{x: 0.9, y: 0.}, Point{x: 0.1, y: 0.},
+			Point{x: 50., y: 50.}
+		];
+
+		let (a, b) = homogenize(&conf, &s1, &s2);
+		assert_eq!(b, s1);
+		assert_eq!(blx_alpha(&a, &b, 0.5), s1);
+
+		// Without alignment the child mixes up sensors
+		assert!(blx_alpha(&s1, &s2, 0.5) != s1);
+
+		let mut states = vec![s2.clone()];
+		align_to(&conf, &s1, &mut states);
+		assert_eq!(states[0], s1);
+	}
+
+	#[test]
+	fn test_homogenize_sectors() {
+		let mut conf = Configuration::new();
+		conf.w = 60.; conf.h = 100.;
+		conf.n = 3;
+		conf.counts = vec![1, 2];
+		conf.radius = vec![10., 20.];
+		conf.fov = vec![FULL_CIRCLE, 1.];
+
+		let s1 = vec![
+			Point{x: 5., y: 5.}, Point{x: 10., y: 10.}, Point{x: 50., y: 90.},
+			Point{x: 1., y: 0.}, Point{x: 0., y: 1.}
+		];
+		let s2 = vec![
+			Point{x: 5., y: 5.}, Point{x: 50., y: 90.}, Point{x: 10., y: 10.},
+			Point{x: 0., y: 1.}, Point{x: 1., y: 0.}
+		];
+
+		let (_, p2) = homogenize(&conf, &s1, &s2);
+		assert!(Point::all_equal(&s1, &p2));
+	}
 }
 
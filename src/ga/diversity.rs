@@ -0,0 +1,165 @@
+use std::f32;
+
+use models::*;
+use models::point::Point;
+use ga::cross::homogenize;
+use ga::mating::genotype_distance;
+
+/// How the GA keeps the pool from collapsing onto one layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Diversity {
+	None,
+	/// Divides the score of each state by the number of states within the niche radius,
+	/// weighted by how close they are
+	Sharing(f32),
+	/// Pairs of children replace the parent they are closest to, when they score at least as well
+	Crowding
+}
+
+impl Diversity {
+	pub fn name(&self) -> String {
+		match *self {
+			Diversity::None => String::from("none"),
+			Diversity::Sharing(r) => format!("sharing {}", r),
+			Diversity::Crowding => String::from("crowding")
+		}
+	}
+}
+
+/// Genotype distance with the sensors of each type matched optimally,
+/// so that layouts listing the same sensors in another order are at distance zero
+pub fn matched_distance(conf: &Configuration, a: &Vec<Point>, b: &Vec<Point>) -> f32 {
+	let (_, aligned) = homogenize(conf, a, b);
+	genotype_distance(a, &aligned)
+}
+
+/// Mean matched distance of the pool to its first state, per sensor
+pub fn pool_diversity(conf: &Configuration, pool: &[Vec<Point>]) -> f32 {
+	if pool.len() < 2 || conf.n == 0 {
+		return 0.;
+	}
+	let total: f32 = pool[1..].iter().map(|s| matched_distance(conf, &pool[0], s)).sum();
+	total / (pool.len() - 1) as f32 / conf.n as f32
+}
+
+/// Scores divided by the niche count of each state. States closer than `radius`
+/// share their score with a triangular sharing function. Negative scores are shifted
+/// up to zero first, since dividing them would favour crowded states.
+pub fn shared_scores(conf: &Configuration, pool: &[Vec<Point>], scores: &[f32], radius: f32) -> Vec<f32> {
+	let n = pool.len();
+	let mut niche = vec![1.; n];
+	for i in 0..n {
+		for j in i+1..n {
+			let d = matched_distance(conf, &pool[i], &pool[j]);
+			if d < radius {
+				let sh = 1. - d / radius;
+				niche[i] += sh;
+				niche[j] += sh;
+			}
+		}
+	}
+	let low = scores.iter().cloned().fold(0., f32::min);
+	(0..n).map(|i| (scores[i] - low) / niche[i]).collect()
+}
+
+/// Indices of up to `size` states in order of `keys`, best first, skipping states
+/// within `threshold` of a state already kept. Skipped states fill the rest when
+/// too few distinct states remain.
+pub fn distinct(conf: &Configuration, pool: &[Vec<Point>], keys: &[f32],
+		threshold: f32, size: usize) -> Vec<usize> {
+	let mut order: Vec<usize> = (0..pool.len()).collect();
+	order.sort_by(|a, b| keys[*b].partial_cmp(&keys[*a]).unwrap());
+
+	let mut kept: Vec<usize> = Vec::new();
+	let mut skipped: Vec<usize> = Vec::new();
+	for i in order {
+		if kept.len() == size {
+			break;
+		}
+		if kept.iter().any(|k| matched_distance(conf, &pool[*k], &pool[i]) <= threshold) {
+			skipped.push(i);
+		} else {
+			kept.push(i);
+		}
+	}
+	let missing = size - kept.len();
+	kept.extend(skipped.into_iter().take(missing));
+	kept
+}
+
+/// Keeps `size` states by shared score when sharing, dropping near-duplicates when
+/// `threshold` is set. Returns the kept states best first with their unshared scores.
+pub fn select(conf: &Configuration, pool: &[Vec<Point>], scores: &[f32], diversity: Diversity,
+		threshold: Option<f32>, size: usize) -> (Vec<Vec<Point>>, Vec<f32>) {
+	let keys = match diversity {
+		Diversity::Sharing(radius) => shared_scores(conf, pool, scores, radius),
+		_ => scores.to_vec()
+	};
+	let kept = match threshold {
+		Some(t) => distinct(conf, pool, &keys, t, size),
+		None => {
+			let mut order: Vec<usize> = (0..pool.len()).collect();
+			order.sort_by(|a, b| keys[*b].partial_cmp(&keys[*a]).unwrap());
+			order.truncate(size);
+			order
+		}
+	};
+	(kept.iter().map(|i| pool[*i].clone()).collect(), kept.iter().map(|i| scores[*i]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn conf() -> Configuration {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 3;
+		conf.counts = vec![1, 2];
+		conf.radius = vec![10., 20.];
+		conf
+	}
+
+	#[test]
+	fn test_matched_distance() {
+		let conf = conf();
+		let a = vec![Point::wrap(10.), Point{x: 20., y: 80.}, Point{x: 80., y: 20.}];
+		let b = vec![Point::wrap(10.), Point{x: 80., y: 20.}, Point{x: 20., y: 80.}];
+		let c = vec![Point::wrap(13.), Point{x: 80., y: 24.}, Point{x: 20., y: 80.}];
+
+		assert!(genotype_distance(&a, &b) > 100.);
+		assert_eq!(matched_distance(&conf, &a, &b), 0.);
+		assert!((matched_distance(&conf, &a, &c) - (18f32.sqrt() + 4.)).abs() < 1e-4);
+		assert_eq!(pool_diversity(&conf, &[a.clone(), b.clone()]), 0.);
+	}
+
+	#[test]
+	fn test_select() {
+		let conf = conf();
+		let a = vec![Point::wrap(10.), Point{x: 20., y: 80.}, Point{x: 80., y: 20.}];
+		let b = vec![Point::wrap(10.), Point{x: 80., y: 20.}, Point{x: 20., y: 80.}];
+		let c = vec![Point::wrap(12.), Point{x: 80., y: 20.}, Point{x: 20., y: 80.}];
+		let d = vec![Point::wrap(50.), Point{x: 50., y: 20.}, Point{x: 50., y: 80.}];
+		let pool = vec![a, b, c, d];
+		let scores = vec![1., 0.9, 0.8, 0.5];
+
+		// b is a reordered copy of a and c is close to it
+		let (_, s) = select(&conf, &pool, &scores, Diversity::None, Some(0.), 2);
+		assert_eq!(s, vec![1., 0.8]);
+		let (_, s) = select(&conf, &pool, &scores, Diversity::None, Some(5.), 2);
+		assert_eq!(s, vec![1., 0.5]);
+
+		// Three states share one niche, so the lone one ranks first
+		let shared = shared_scores(&conf, &pool, &scores, 10.);
+		assert!(shared[3] > shared[0]);
+		let (kept, s) = select(&conf, &pool, &scores, Diversity::Sharing(10.), None, 2);
+		assert_eq!(s, vec![0.5, 1.]);
+		assert_eq!(kept[0], pool[3]);
+
+		// Sharing still favours the lone state when scores are negative
+		let negative = vec![-0.1, -0.2, -1., -0.3];
+		let shared = shared_scores(&conf, &pool, &negative, 10.);
+		assert!(shared.iter().all(|s| *s >= 0.));
+		assert!(shared[3] > shared[0]);
+	}
+}
@@ -22,7 +22,8 @@ impl<T: Clone+Debug> HugarianSolver<T> {
 		let mut _lx: Vec<f32> = Vec::new();
 		let mut _ly: Vec<f32> = Vec::new();
 		for i in 0.._n {
-			_lx.push(0.); _ly.push(0.);
+			// Labels start at the heaviest edge, which may be negative
+			_lx.push(f32::MIN); _ly.push(0.);
 			for j in 0.._n {
 				_lx[i] = f32::max(_lx[i], weight_fn(&g1[i], &g2[j]));
 			}
@@ -46,6 +47,12 @@ impl<T: Clone+Debug> HugarianSolver<T> {
 			return true;
 		}
 
+		// Each phase grows a fresh alternating tree
+		self.s = vec![false; self.n as usize];
+		self.t = vec![false; self.n as usize];
+		self.prev = vec![-1; self.n as usize];
+		self.q.clear();
+
 		// Find root
 		let mut root: i32 = 0;
 		let mut x = 0;
@@ -87,7 +94,9 @@ impl<T: Clone+Debug> HugarianSolver<T> {
 				y = 0;
 				while y < self.n {
 					let _y = y as usize;
-					if (weight_fn(&self.g1[_x], &self.g2[_y]) == self.lx[_x] + self.ly[_y]) && !self.t[_y] {
+					let w = weight_fn(&self.g1[_x], &self.g2[_y]);
+					let tight = (w - (self.lx[_x] + self.ly[_y])).abs() <= 1e-4 * f32::max(1., w.abs());
+					if tight && !self.t[_y] {
 						if self.yx[_y] == -1 {
 							// Found exposed vertex
 							stop = true;
@@ -230,4 +239,39 @@ mod tests {
 		println!("{:?}", v1);
 		println!("{:?}", v2);
 	}
+
+	fn permutations(n: usize) -> Vec<Vec<usize>> {
+		if n == 0 {
+			return vec![Vec::new()];
+		}
+		let mut all = Vec::new();
+		for p in permutations(n - 1) {
+			for i in 0..n {
+				let mut q = p.clone();
+				q.insert(i, n - 1);
+				all.push(q);
+			}
+		}
+		all
+	}
+
+	#[test]
+	fn test_hungarian_optimal() {
+		use rand::distributions::{Range, IndependentSample};
+		use random;
+		let mut r = random::thread_rng();
+		let rng = Range::new(0., 100.);
+
+		for _ in 0..20 {
+			let mut v1: Vec<Point> = (0..6).map(|_| Point{x: rng.ind_sample(&mut r), y: rng.ind_sample(&mut r)}).collect();
+			let mut v2: Vec<Point> = (0..6).map(|_| Point{x: rng.ind_sample(&mut r), y: rng.ind_sample(&mut r)}).collect();
+			let best = permutations(6).iter()
+				.map(|p| (0..6).map(|i| v1[i].distance(&v2[p[i]])).sum::<f32>())
+				.fold(f32::MAX, f32::min);
+
+			apply_hungarian(&mut v1, &mut v2, &|p1, p2| -p1.distance(p2));
+			let total: f32 = (0..6).map(|i| v1[i].distance(&v2[i])).sum();
+			assert!((total - best).abs() < 1e-2, "{} vs {}", total, best);
+		}
+	}
 }
@@ -1,13 +1,15 @@
+use std::f32;
 use rand::distributions::{Range, IndependentSample};
 use rand::Rng;
-use rand;
+use random;
 
 use models::*;
 use models::point::Point;
+use models::network::Network;
 
 pub fn random_points(conf: &Configuration, size: i32) -> Vec<Point> {
 	let mut state: Vec<Point> = Vec::new();
-	let mut r = rand::thread_rng();
+	let mut r = random::thread_rng();
 	let rngx = Range::new(0 as f64, conf.w as f64);
 	let rngy = Range::new(0 as f64, conf.h as f64);
 
@@ -22,8 +24,41 @@ pub fn random_points(conf: &Configuration, size: i32) -> Vec<Point> {
 	state
 }
 
+/// Creates a random unit heading gene for each sector sensor
+pub fn random_headings(conf: &Configuration) -> Vec<Point> {
+	let mut headings: Vec<Point> = Vec::new();
+	let mut r = random::thread_rng();
+	let rng = Range::new(-f32::consts::PI, f32::consts::PI);
+
+	for _ in 0..conf.sector_count() {
+		headings.push(Point::from_angle(rng.ind_sample(&mut r)));
+	}
+
+	headings
+}
+
+/// Creates a random activation gene for each sensor when counts are variable
+pub fn random_flags(conf: &Configuration) -> Vec<Point> {
+	let mut flags: Vec<Point> = Vec::new();
+	if !conf.variable_counts {
+		return flags;
+	}
+	let mut r = random::thread_rng();
+	let rng = Range::new(0., 1.);
+
+	for _ in 0..conf.n {
+		flags.push(Point{x: rng.ind_sample(&mut r), y: 0.});
+	}
+
+	flags
+}
+
 pub fn random_state(conf: &Configuration) -> Vec<Point> {
-	random_points(conf, conf.n)
+	let mut state = random_points(conf, conf.n);
+	state.extend(random_headings(conf));
+	state.extend(random_flags(conf));
+	state.extend(random_points(conf, conf.free_sinks));
+	state
 }
 
 pub fn random_init(conf: &Configuration, size: i32) -> Vec<Vec<Point>> {
@@ -45,10 +80,13 @@ pub fn heuristic_state(conf: &Configuration) -> Vec<Point> {
 	for _ in 0..conf.n {
 		state.push(Point::wrap(0.));
 	}
+	state.extend(random_headings(conf));
+	state.extend(random_flags(conf));
+	state.extend(random_points(conf, conf.free_sinks));
 
-	let mut r = rand::thread_rng();
+	let mut r = random::thread_rng();
 	let circles = Circle::from_state(conf, &state);
-	let mut indices: Vec<usize> = (0..state.len()).collect();
+	let mut indices: Vec<usize> = (0..circles.len() - conf.fixed.len()).collect();
 
 	r.shuffle(&mut indices);
 	let mut current = Point::wrap(0.);
@@ -81,6 +119,97 @@ pub fn heuristic_init(conf: &Configuration, size: i32) -> Vec<Vec<Point>> {
 	states
 }
 
+/// How the states of the first generation are created
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Initializer {
+	Random,
+	/// Sensors packed row by row in random order
+	Heuristic
+}
+
+impl Initializer {
+	pub fn from_name(name: &str) -> Option<Initializer> {
+		match name {
+			"random" => Some(Initializer::Random),
+			"heuristic" => Some(Initializer::Heuristic),
+			_ => None
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Initializer::Random => "random",
+			Initializer::Heuristic => "heuristic"
+		}
+	}
+
+	pub fn init(&self, conf: &Configuration, size: i32) -> Vec<Vec<Point>> {
+		match *self {
+			Initializer::Random => random_init(conf, size),
+			Initializer::Heuristic => heuristic_init(conf, size)
+		}
+	}
+}
+
+/// First generation made of the seed layouts, filled up to `size` by the initializer
+pub fn seeded_init(conf: &Configuration, seeds: &[Vec<Point>], size: i32,
+		initializer: Initializer) -> Vec<Vec<Point>> {
+	let mut states: Vec<Vec<Point>> = seeds.iter()
+		.take(size as usize)
+		.cloned()
+		.collect();
+	for s in &states {
+		assert_eq!(s.len(), conf.genome_len(), "Seed layout does not match the configuration");
+	}
+	normalize(conf, &mut states);
+
+	let rest = size - states.len() as i32;
+	states.extend(initializer.init(conf, rest));
+	states
+}
+
+/// Maps a saved layout onto the genome of `conf`. The layout may hold
+/// fewer or more sensors of each type, as described by `layout_conf`.
+/// Missing sensors are placed at random and, with variable counts, start inactive.
+pub fn expand_layout(conf: &Configuration, layout_conf: &Configuration, layout: &[Point]) -> Vec<Point> {
+	let mut state = random_state(conf);
+	let mut _i = 0;
+	let mut _s = conf.n as usize;
+	let mut _li = 0;
+	let mut _ls = layout_conf.n as usize;
+	for t in 0..conf.counts.len() {
+		let count = conf.counts[t] as usize;
+		let saved = *layout_conf.counts.get(t).unwrap_or(&0) as usize;
+		for j in 0..count {
+			if j < saved {
+				state[_i + j] = layout[_li + j];
+				if conf.is_sector(t) {
+					state[_s + j] = layout[_ls + j];
+				}
+			}
+			if conf.variable_counts {
+				let on = if j < saved { 1. } else { 0. };
+				state[conf.flag_genes() + _i + j] = Point{x: on, y: 0.};
+			}
+		}
+		_i += count;
+		_li += saved;
+		if conf.is_sector(t) {
+			_s += count;
+		}
+		if layout_conf.is_sector(t) {
+			_ls += saved;
+		}
+	}
+
+	// Free sinks keep their saved position when the layout has them
+	let sinks = &layout[layout_conf.sink_genes()..];
+	for (k, p) in sinks.iter().take(conf.free_sinks as usize).enumerate() {
+		state[conf.sink_genes() + k] = *p;
+	}
+	state
+}
+
 /// Applies the virtual force algorithm to a set of states
 pub fn vfa_batch(conf: &Configuration, states: &mut Vec<Vec<Point>>) {
 	for i in 0..states.len() {
@@ -96,16 +225,16 @@ pub fn vfa(conf: &Configuration, state: &mut Vec<Point>) {
 
 	let circles = Circle::from_state(conf, state);
 
-	// Adds boundary edges to list of candidates
+	// Adds boundary edges to list of candidates. Fixed sensors push and pull but never move.
 	let mut cand = circles.clone();
 	cand.extend(vec![
-		Circle{center: Point{x: 0., y: 0.}, radius: 0.},
-		Circle{center: Point{x: 0., y: conf.h}, radius: 0.},
-		Circle{center: Point{x: conf.w, y: 0.}, radius: 0.},
-		Circle{center: Point{x: conf.w, y: conf.h}, radius: 0.} 
+		Circle::new(Point{x: 0., y: 0.}, 0.),
+		Circle::new(Point{x: 0., y: conf.h}, 0.),
+		Circle::new(Point{x: conf.w, y: 0.}, 0.),
+		Circle::new(Point{x: conf.w, y: conf.h}, 0.) 
 	]);
 
-	for i in 0..circles.len() {
+	for i in 0..circles.len() - conf.fixed.len() {
 		let c = &circles[i];
 		let mut fpull = Point::wrap(0.); 
 		let mut fpush = Point::wrap(0.);
@@ -168,6 +297,80 @@ pub fn normalize(conf: &Configuration, states: &mut Vec<Vec<Point>>) {
 			}
 			_i += count;
 		}
+
+		// Keep heading genes on the unit circle
+		for g in &mut state[conf.n as usize..conf.flag_genes()] {
+			*g = Point::from_angle(g.angle());
+		}
+
+		// Keep activation genes in [0, 1]
+		for g in &mut state[conf.flag_genes()..conf.sink_genes()] {
+			*g = Point{x: g.x.clamp(0., 1.), y: 0.};
+		}
+
+		// Keep free sinks inside the field
+		for p in &mut state[conf.sink_genes()..] {
+			p.x = f32::min(f32::max(p.x, 0.), conf.w);
+			p.y = f32::min(f32::max(p.y, 0.), conf.h);
+		}
+	}
+}
+
+/// Moves sensors outside the main network into communication range of it,
+/// closest first, until all sensors are connected
+pub fn repair_connectivity(conf: &Configuration, states: &mut Vec<Vec<Point>>, require_sink: bool) {
+	for state in states {
+		let mut net = Network::from_state(conf, state);
+		if require_sink && net.positions.len() == net.sensors {
+			continue;
+		}
+		let mut main = net.connected(require_sink);
+		// Sinks belong to the main network when a path to them is required
+		for _ in net.sensors..net.positions.len() {
+			main.push(require_sink);
+		}
+
+		loop {
+			// Find the closest pair of outside sensor and main node
+			let mut pair: Option<(usize, usize)> = None;
+			let mut gap = f32::MAX;
+			for i in 0..net.sensors {
+				if main[i] {
+					continue;
+				}
+				for (j, in_main) in main.iter().enumerate() {
+					if !in_main {
+						continue;
+					}
+					let g = net.positions[i].distance(&net.positions[j]) - net.link_range(i, j);
+					if g < gap {
+						gap = g;
+						pair = Some((i, j));
+					}
+				}
+			}
+			let (i, j) = match pair {
+				Some(p) => p,
+				None => break
+			};
+
+			if gap > 0. {
+				let (pi, pj) = (net.positions[i], net.positions[j]);
+				let d = pi.distance(&pj);
+				let scale = Point::wrap(0.99 * net.link_range(i, j) / d);
+				net.positions[i] = pj + (pi - pj) * scale;
+				state[i] = net.positions[i];
+				net.build_links();
+			}
+
+			// Everything reachable from the moved sensor joins the main network
+			let hops = net.hops_from(&[i]);
+			for k in 0..main.len() {
+				if hops[k].is_some() {
+					main[k] = true;
+				}
+			}
+		}
 	}
 }
 
@@ -199,6 +402,86 @@ mod tests {
 		assert_eq!(v[0].len(), 3);
 	}
 
+	#[test]
+	fn test_seeded_init() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 3;
+		conf.counts = vec![1, 2];
+		conf.radius = vec![10., 20.];
+		let seed = vec![Point::wrap(50.), Point{x: 20., y: 80.}, Point{x: 80., y: 20.}];
+
+		let v = seeded_init(&conf, std::slice::from_ref(&seed), 5, Initializer::Random);
+		assert_eq!(v.len(), 5);
+		assert!(Point::all_equal(&v[0], &seed));
+	}
+
+	#[test]
+	fn test_expand_layout() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 4;
+		conf.counts = vec![2, 2];
+		conf.radius = vec![10., 20.];
+		conf.fov = vec![FULL_CIRCLE, 1.];
+		conf.variable_counts = true;
+
+		// One sensor of each type was deployed
+		let mut saved_conf = conf.clone();
+		saved_conf.variable_counts = false;
+		saved_conf.counts = vec![1, 1];
+		saved_conf.n = 2;
+		let saved = vec![Point::wrap(30.), Point::wrap(60.), Point{x: 0., y: 1.}];
+
+		let s = expand_layout(&conf, &saved_conf, &saved);
+		assert_eq!(s.len(), conf.genome_len());
+		let (active, deployed) = conf.active_layout(&s);
+		assert_eq!(active.counts, vec![1, 1]);
+		assert!(Point::all_equal(&deployed, &saved));
+	}
+
+	#[test]
+	fn test_sector_state() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 3;
+		conf.counts = vec![1, 2];
+		conf.radius = vec![10., 20.];
+		conf.fov = vec![FULL_CIRCLE, 1.];
+
+		let mut v = heuristic_init(&conf, 2);
+		v.extend(random_init(&conf, 2));
+		for s in &v {
+			assert_eq!(s.len(), 5);
+		}
+
+		v[0][3] = Point{x: 3., y: 4.};
+		normalize(&conf, &mut v);
+		assert!((v[0][3].x - 0.6).abs() < 1e-6);
+		assert!((v[0][3].y - 0.8).abs() < 1e-6);
+	}
+
+	#[test]
+	fn test_repair_connectivity() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 10;
+		conf.counts = vec![10];
+		conf.radius = vec![5.];
+
+		let mut v = random_init(&conf, 5);
+		repair_connectivity(&conf, &mut v, false);
+		for s in &v {
+			assert_eq!(Network::from_state(&conf, s).component_count(), 1);
+		}
+
+		conf.sinks = vec![Point{x: 0., y: 0.}];
+		repair_connectivity(&conf, &mut v, true);
+		for s in &v {
+			assert_eq!(Network::from_state(&conf, s).connected_fraction(true), 1.);
+		}
+	}
+
 	#[test]
 	fn test_random_state() {
 		let mut conf = Configuration::new();
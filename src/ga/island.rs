@@ -0,0 +1,195 @@
+use std::f32;
+use std::thread;
+use rand::Rng;
+
+use models::*;
+use models::point::Point;
+use ga::*;
+
+/// Which islands receive the migrants of an island
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Topology {
+	/// Island i sends to island i + 1, the last one to the first
+	Ring,
+	/// Every island sends to every other island
+	Full
+}
+
+impl Topology {
+	pub fn from_name(name: &str) -> Option<Topology> {
+		match name {
+			"ring" => Some(Topology::Ring),
+			"full" => Some(Topology::Full),
+			_ => None
+		}
+	}
+
+	/// Islands that island `i` of `m` sends migrants to
+	pub fn targets(&self, i: usize, m: usize) -> Vec<usize> {
+		match *self {
+			Topology::Ring => if m > 1 { vec![(i + 1) % m] } else { Vec::new() },
+			Topology::Full => (0..m).filter(|j| *j != i).collect()
+		}
+	}
+}
+
+/// Settings of the island model. Each island evolves with its own GA settings.
+#[derive(Debug, Clone)]
+pub struct Islands {
+	pub params: Vec<Params>,
+	/// Generations between migrations
+	pub interval: i32,
+	/// Number of best states each island sends along every edge
+	pub migrants: usize,
+	pub topology: Topology
+}
+
+/// Copies the best states of every island over the worst states of its targets.
+/// Scored pools are ranked by their scores first, since sharing orders them by shared score.
+pub fn migrate(runs: &mut [Checkpoint], migrants: usize, topology: Topology) {
+	let m = runs.len();
+	for run in runs.iter_mut() {
+		if run.scores.len() == run.pool.len() {
+			sort_pool(&mut run.pool, &mut run.scores);
+		}
+	}
+	let emigrants: Vec<Vec<Vec<Point>>> = runs.iter()
+		.map(|run| run.pool.iter().take(migrants).cloned().collect())
+		.collect();
+
+	let mut incoming: Vec<Vec<Vec<Point>>> = vec![Vec::new(); m];
+	for (i, states) in emigrants.iter().enumerate() {
+		for j in topology.targets(i, m) {
+			incoming[j].extend(states.iter().cloned());
+		}
+	}
+
+	for (run, states) in runs.iter_mut().zip(incoming) {
+		// An island always keeps its own best state
+		let k = usize::min(states.len(), run.pool.len() - 1);
+		let keep = run.pool.len() - k;
+		run.pool.truncate(keep);
+		run.pool.extend(states.into_iter().take(k));
+		run.scores.clear();
+	}
+}
+
+/// Runs one GA per island in parallel threads, with migrations every
+/// `interval` generations. Each island draws from its own random stream,
+/// derived from the caller's, so results do not depend on thread scheduling.
+pub fn island_model(conf: &Configuration, islands: &Islands) -> Vec<Point> {
+	assert!(!islands.params.is_empty(), "The island model needs at least one island");
+	assert!(islands.interval > 0, "The migration interval must be at least one generation");
+	let mut r = random::thread_rng();
+	let mut runs: Vec<Checkpoint> = islands.params.iter()
+		.map(|p| Checkpoint::new(p, random::from_seed(r.next_u64())))
+		.collect();
+	let iters = islands.params.iter().map(|p| p.generations()).max().unwrap_or(0);
+
+	let mut done = 0;
+	while done < iters {
+		let epoch = i32::min(islands.interval, iters - done);
+		thread::scope(|s| {
+			for (i, run) in runs.iter_mut().enumerate() {
+				s.spawn(move || {
+					random::restore(run.rng);
+					if run.pool.is_empty() {
+						let p = &run.params;
+						run.pool = init::seeded_init(conf, &p.seeds, p.size, p.init);
+						if p.connectivity == Connectivity::Repair {
+							init::repair_connectivity(conf, &mut run.pool, p.require_sink);
+						}
+					}
+					for _ in 0..epoch {
+						if run.generation < run.params.generations() {
+							generation(conf, run);
+						}
+					}
+					run.rng = random::state();
+					println!("Island {} generation {}: Best score = {:e}, diversity = {}", i, run.generation,
+						run.best_score, diversity::pool_diversity(conf, &run.pool));
+				});
+			}
+		});
+		done += epoch;
+
+		if done < iters {
+			println!("Migrating...");
+			migrate(&mut runs, islands.migrants, islands.topology);
+		}
+	}
+
+	// Islands may weigh layouts differently, so they are compared by the first island's objective
+	let judge = &islands.params[0];
+	let mut best_state: Vec<Point> = Vec::new();
+	let mut best_score = f32::MIN;
+	for run in &runs {
+		let score = judge.score(conf, &run.best_state);
+		if score > best_score {
+			best_score = score;
+			best_state = run.best_state.clone();
+		}
+	}
+	best_state
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_migrate() {
+		let state = |x: f32| vec![Point::wrap(x)];
+		let mut runs: Vec<Checkpoint> = (0..3)
+			.map(|i| {
+				let mut run = Checkpoint::new(&Params::new(), [1, 0]);
+				run.pool = vec![state(i as f32), state(10. + i as f32), state(20. + i as f32)];
+				run
+			})
+			.collect();
+
+		migrate(&mut runs, 1, Topology::Ring);
+		assert_eq!(runs[1].pool, vec![state(1.), state(11.), state(0.)]);
+		assert_eq!(runs[0].pool[2], state(2.));
+
+		migrate(&mut runs, 1, Topology::Full);
+		assert_eq!(runs[0].pool, vec![state(0.), state(1.), state(2.)]);
+
+		// Scored pools send their best state, wherever it sits
+		for run in runs.iter_mut() {
+			run.scores = vec![1., 3., 2.];
+		}
+		let best: Vec<Vec<Point>> = runs.iter().map(|run| run.pool[1].clone()).collect();
+		migrate(&mut runs, 1, Topology::Ring);
+		assert_eq!(runs[1].pool[0], best[1]);
+		assert_eq!(runs[1].pool[2], best[0]);
+	}
+
+	#[test]
+	fn test_island_model() {
+		let mut conf = Configuration::new();
+		conf.w = 60.; conf.h = 100.;
+		conf.n = 3;
+		conf.counts = vec![1, 2];
+		conf.radius = vec![10., 20.];
+
+		let mut params = Params::new();
+		params.size = 8; params.iters = 6;
+		params.objective = Objective::Coverage;
+		let mut other = params.clone();
+		other.cross_ratio = 0.9; other.mutate_ratio = 0.3;
+		let islands = Islands {
+			params: vec![params.clone(), other, params],
+			interval: 2,
+			migrants: 2,
+			topology: Topology::Ring
+		};
+
+		random::seed(5);
+		let a = island_model(&conf, &islands);
+		random::seed(5);
+		let b = island_model(&conf, &islands);
+		assert_eq!(a.len(), 3);
+		assert_eq!(a, b);
+	}
+}
@@ -0,0 +1,137 @@
+use rand::Rng;
+use rand::distributions::{IndependentSample, Range};
+
+use models::Configuration;
+use models::point::Point;
+use ga::select::tournament;
+use ga::diversity::matched_distance;
+
+/// Number of candidate mates compared by assortative and disassortative mating
+pub const CANDIDATES: usize = 5;
+
+/// How parents are paired for crossover
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mating {
+	/// Every pair of the pool crosses with probability `cross_ratio`
+	AllPairs,
+	/// Both parents are picked by binary tournament
+	Selection,
+	/// Both parents are picked uniformly
+	Random,
+	/// The first parent mates with the closest of a few random candidates, see `matched_distance`
+	Assortative,
+	/// The first parent mates with the farthest of a few random candidates
+	Disassortative
+}
+
+impl Mating {
+	pub fn from_name(name: &str) -> Option<Mating> {
+		match name {
+			"all-pairs" => Some(Mating::AllPairs),
+			"selection" => Some(Mating::Selection),
+			"random" => Some(Mating::Random),
+			"assortative" => Some(Mating::Assortative),
+			"disassortative" => Some(Mating::Disassortative),
+			_ => None
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Mating::AllPairs => "all-pairs",
+			Mating::Selection => "selection",
+			Mating::Random => "random",
+			Mating::Assortative => "assortative",
+			Mating::Disassortative => "disassortative"
+		}
+	}
+}
+
+/// Sum of the distances between genes at the same position
+pub fn genotype_distance(a: &[Point], b: &[Point]) -> f32 {
+	a.iter().zip(b.iter()).map(|(p, q)| p.distance(q)).sum()
+}
+
+/// Picks `count` parent pairs from the pool. `scores` are only read by `Mating::Selection`.
+/// `Mating::AllPairs` has no fixed count and returns every pair.
+pub fn pairs<R: Rng>(conf: &Configuration, mating: Mating, pool: &[Vec<Point>], scores: &[f32],
+		count: usize, r: &mut R) -> Vec<(usize, usize)> {
+	let n = pool.len();
+	let pick = Range::new(0, n);
+	let mut v: Vec<(usize, usize)> = Vec::new();
+	if mating == Mating::AllPairs {
+		for i in 0..n {
+			for j in i+1..n {
+				v.push((i, j));
+			}
+		}
+		return v;
+	}
+
+	for _ in 0..count {
+		let pair = match mating {
+			Mating::Selection => (tournament(scores, 2, r), tournament(scores, 2, r)),
+			Mating::Assortative | Mating::Disassortative => {
+				let a = pick.ind_sample(r);
+				let mut mate = None;
+				for _ in 0..CANDIDATES {
+					let b = pick.ind_sample(r);
+					if b == a && n > 1 {
+						continue;
+					}
+					let d = matched_distance(conf, &pool[a], &pool[b]);
+					let better = match mate {
+						None => true,
+						Some((_, best)) => if mating == Mating::Assortative { d < best } else { d > best }
+					};
+					if better {
+						mate = Some((b, d));
+					}
+				}
+				(a, mate.map_or(pick.ind_sample(r), |(b, _)| b))
+			},
+			_ => (pick.ind_sample(r), pick.ind_sample(r))
+		};
+		v.push(pair);
+	}
+	v
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use random;
+
+	#[test]
+	fn test_pairs() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 1;
+		conf.counts = vec![1];
+		conf.radius = vec![5.];
+		let pool: Vec<Vec<Point>> = (0..6).map(|i| vec![Point::wrap(i as f32 * 10.)]).collect();
+		let scores: Vec<f32> = (0..6).map(|i| i as f32).collect();
+		let mut r = random::thread_rng();
+
+		assert_eq!(pairs(&conf, Mating::AllPairs, &pool, &scores, 3, &mut r).len(), 15);
+		for m in &[Mating::Selection, Mating::Random, Mating::Assortative, Mating::Disassortative] {
+			let v = pairs(&conf, *m, &pool, &scores, 40, &mut r);
+			assert_eq!(v.len(), 40);
+			assert!(v.iter().all(|&(a, b)| a < 6 && b < 6));
+		}
+
+		// Close mates are preferred, so most pairs are neighbours
+		let v = pairs(&conf, Mating::Assortative, &pool, &scores, 200, &mut r);
+		let far = pairs(&conf, Mating::Disassortative, &pool, &scores, 200, &mut r);
+		let gap = |v: &Vec<(usize, usize)>| -> f32 {
+			v.iter().map(|&(a, b)| genotype_distance(&pool[a], &pool[b])).sum::<f32>() / v.len() as f32
+		};
+		println!("assortative {} disassortative {}", gap(&v), gap(&far));
+		assert!(gap(&v) < gap(&far));
+
+		// Tournaments favour high scores
+		let v = pairs(&conf, Mating::Selection, &pool, &scores, 200, &mut r);
+		let mean = v.iter().map(|&(a, _)| a as f32).sum::<f32>() / 200.;
+		assert!(mean > 2.5);
+	}
+}
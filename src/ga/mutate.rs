@@ -1,11 +1,11 @@
 use rand::distributions::{IndependentSample, Normal};
-use rand;
+use random;
 
 use models::point::Point;
 
 pub fn dynamic_gaussian(state: &Vec<Point>, p1: &Vec<Point>, p2: &Vec<Point>) -> Vec<Point> {
 	let mut new_state = state.clone();
-	let mut r = rand::thread_rng();
+	let mut r = random::thread_rng();
 	let mean = 0.;
 
 	for i in 0..new_state.len() {
@@ -20,7 +20,7 @@ pub fn dynamic_gaussian(state: &Vec<Point>, p1: &Vec<Point>, p2: &Vec<Point>) ->
 
 pub fn static_gaussian(state: &Vec<Point>, _p1: &Vec<Point>, _p2: &Vec<Point>) -> Vec<Point> {
 	let mut new_state = state.clone();
-	let mut r = rand::thread_rng();
+	let mut r = random::thread_rng();
 	let mean = 0.;
 	let sdev = 50.;
 
@@ -0,0 +1,322 @@
+use std::f32;
+use random;
+use rand::distributions::{IndependentSample, Range};
+
+use models::*;
+use models::adaptive::*;
+use models::point::Point;
+use models::raster::*;
+use models::network::Network;
+use ga::*;
+use ga::cross::*;
+use ga::mutate::*;
+use ga::redeploy::*;
+
+/// An objective of the multi-objective search. Values are oriented so that higher is better.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Criterion {
+	Coverage,
+	Overlap,
+	Connectivity,
+	/// Total travel from the reference layout
+	Displacement,
+	/// Longest single travel from the reference layout. Every evaluation bisects
+	/// over the n^2 candidate distances and solves an assignment at each step,
+	/// so expect O(n^3 log n) per state, much slower than the other criteria.
+	MaxDisplacement
+}
+
+impl Criterion {
+	pub fn from_name(name: &str) -> Option<Criterion> {
+		match name {
+			"coverage" => Some(Criterion::Coverage),
+			"overlap" => Some(Criterion::Overlap),
+			"connectivity" => Some(Criterion::Connectivity),
+			"displacement" => Some(Criterion::Displacement),
+			"max-displacement" => Some(Criterion::MaxDisplacement),
+			_ => None
+		}
+	}
+
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Criterion::Coverage => "coverage",
+			Criterion::Overlap => "overlap",
+			Criterion::Connectivity => "connectivity",
+			Criterion::Displacement => "displacement",
+			Criterion::MaxDisplacement => "max-displacement"
+		}
+	}
+
+	/// Converts a value back to the measured quantity
+	pub fn measured(&self, value: f32) -> f32 {
+		match *self {
+			Criterion::Overlap | Criterion::Displacement | Criterion::MaxDisplacement => -value,
+			_ => value
+		}
+	}
+
+	/// Value of the criterion. Overlap and displacement are negated.
+	pub fn value(&self, conf: &Configuration, params: &Params, state: &Vec<Point>,
+			reference: &[Point]) -> f32 {
+		match *self {
+			Criterion::Coverage => {
+				CoverageGrid::from_state(conf, state, DEFAULT_RESOLUTION).coverage_fraction()
+			},
+			Criterion::Overlap => -overlap_area(conf, state),
+			Criterion::Connectivity => {
+				Network::from_state(conf, state).connected_fraction(params.require_sink)
+			},
+			Criterion::Displacement => -displacement(conf, state, reference),
+			Criterion::MaxDisplacement => {
+				-plan_redeployment(conf, reference, state, Movement::Bottleneck).max
+			}
+		}
+	}
+}
+
+/// Total distance the sensors travel from the reference layout,
+/// with sensors of one type assigned to the spots of `state` optimally
+pub fn displacement(conf: &Configuration, state: &[Point], reference: &[Point]) -> f32 {
+	plan_redeployment(conf, reference, state, Movement::Total).total
+}
+
+/// Checks whether `a` is at least as good as `b` everywhere and better somewhere
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+	let mut better = false;
+	for i in 0..a.len() {
+		if a[i] < b[i] {
+			return false;
+		}
+		if a[i] > b[i] {
+			better = true;
+		}
+	}
+	better
+}
+
+/// Splits the population into fronts of mutually non-dominated individuals, best front first
+pub fn non_dominated_sort(scores: &[Vec<f32>]) -> Vec<Vec<usize>> {
+	let n = scores.len();
+	let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); n];
+	let mut counts = vec![0; n];
+	let mut fronts: Vec<Vec<usize>> = vec![Vec::new()];
+
+	for p in 0..n {
+		for q in 0..n {
+			if dominates(&scores[p], &scores[q]) {
+				dominated[p].push(q);
+			} else if dominates(&scores[q], &scores[p]) {
+				counts[p] += 1;
+			}
+		}
+		if counts[p] == 0 {
+			fronts[0].push(p);
+		}
+	}
+
+	let mut i = 0;
+	while !fronts[i].is_empty() {
+		let mut next: Vec<usize> = Vec::new();
+		for p in &fronts[i] {
+			for q in &dominated[*p] {
+				counts[*q] -= 1;
+				if counts[*q] == 0 {
+					next.push(*q);
+				}
+			}
+		}
+		fronts.push(next);
+		i += 1;
+	}
+	fronts.pop();
+
+	fronts
+}
+
+/// Crowding distance of each member of a front, in the order of `front`
+pub fn crowding_distance(scores: &[Vec<f32>], front: &[usize]) -> Vec<f32> {
+	let n = front.len();
+	let mut dist = vec![0.; n];
+	if n == 0 {
+		return dist;
+	}
+
+	// Values of each objective over the front
+	let columns = (0..scores[front[0]].len())
+		.map(|m| front.iter().map(|i| scores[*i][m]).collect::<Vec<f32>>());
+	for value in columns {
+		let mut order: Vec<usize> = (0..n).collect();
+		order.sort_by(|a, b| value[*a].partial_cmp(&value[*b]).unwrap());
+		let lo = value[order[0]];
+		let hi = value[order[n - 1]];
+		dist[order[0]] = f32::INFINITY;
+		dist[order[n - 1]] = f32::INFINITY;
+		if hi == lo {
+			continue;
+		}
+		for k in 1..n-1 {
+			let prev = value[order[k - 1]];
+			let next = value[order[k + 1]];
+			dist[order[k]] += (next - prev) / (hi - lo);
+		}
+	}
+
+	dist
+}
+
+/// Rank and crowding distance of every individual
+fn rank_population(scores: &[Vec<f32>]) -> (Vec<usize>, Vec<f32>, Vec<Vec<usize>>) {
+	let fronts = non_dominated_sort(scores);
+	let mut rank = vec![0; scores.len()];
+	let mut crowding = vec![0.; scores.len()];
+	for (r, front) in fronts.iter().enumerate() {
+		let d = crowding_distance(scores, front);
+		for (k, i) in front.iter().enumerate() {
+			rank[*i] = r;
+			crowding[*i] = d[k];
+		}
+	}
+	(rank, crowding, fronts)
+}
+
+/// Runs NSGA-II and returns the final Pareto front with its objective values
+pub fn nsga2(conf: &Configuration, params: &Params, criteria: &[Criterion],
+		reference: &[Point]) -> (Vec<Vec<Point>>, Vec<Vec<f32>>) {
+	assert!(params.size > 0, "NSGA-II needs a population of at least one state");
+	let size = params.size as usize;
+	let evaluate = |s: &Vec<Point>| -> Vec<f32> {
+		criteria.iter().map(|c| c.value(conf, params, s, reference)).collect()
+	};
+
+	let mut r = random::thread_rng();
+	let rng = Range::new(0., 1.);
+	let pick = Range::new(0, size);
+
+	println!("Initializing states...");
+	let mut pool = init::seeded_init(conf, &params.seeds, params.size, params.init);
+	if params.connectivity == Connectivity::Repair {
+		init::repair_connectivity(conf, &mut pool, params.require_sink);
+	}
+	let mut scores: Vec<Vec<f32>> = pool.iter().map(&evaluate).collect();
+
+	for it in 0..params.iters {
+		let (rank, crowding, _) = rank_population(&scores);
+		// Binary tournament on rank, then crowding distance
+		let mut tournament = || {
+			let (a, b) = (pick.ind_sample(&mut r), pick.ind_sample(&mut r));
+			if rank[a] < rank[b] || (rank[a] == rank[b] && crowding[a] > crowding[b]) { a } else { b }
+		};
+		let mut parents: Vec<(usize, usize)> = Vec::new();
+		for _ in 0..size {
+			parents.push((tournament(), tournament()));
+		}
+
+		let mut children: Vec<Vec<Point>> = Vec::new();
+		for (a, b) in parents {
+			let (s1, s2) = (&pool[a], &pool[b]);
+			let mut ch = if rng.ind_sample(&mut r) < params.cross_ratio {
+				blx_alpha(s1, s2, 0.5)
+			} else {
+				s1.clone()
+			};
+			if rng.ind_sample(&mut r) < params.mutate_ratio {
+				ch = dynamic_gaussian(&ch, s1, s2);
+				init::vfa(conf, &mut ch);
+			}
+			children.push(ch);
+		}
+		init::normalize(conf, &mut children);
+		if params.connectivity == Connectivity::Repair {
+			init::repair_connectivity(conf, &mut children, params.require_sink);
+		}
+
+		for ch in children {
+			scores.push(evaluate(&ch));
+			pool.push(ch);
+		}
+
+		// Fill the next generation front by front, breaking the last one by crowding distance
+		let (_, crowding, fronts) = rank_population(&scores);
+		let mut keep: Vec<usize> = Vec::new();
+		for front in fronts {
+			if keep.len() + front.len() <= size {
+				keep.extend(front);
+			} else {
+				let mut rest = front.clone();
+				rest.sort_by(|a, b| crowding[*b].partial_cmp(&crowding[*a]).unwrap());
+				let missing = size - keep.len();
+				keep.extend_from_slice(&rest[..missing]);
+			}
+			if keep.len() >= size {
+				break;
+			}
+		}
+		pool = keep.iter().map(|i| pool[*i].clone()).collect();
+		scores = keep.iter().map(|i| scores[*i].clone()).collect();
+
+		let first = non_dominated_sort(&scores)[0].len();
+		println!("Generation {}: Pareto front size = {}", it+1, first);
+	}
+
+	// Layouts with identical objective values appear once
+	let fronts = non_dominated_sort(&scores);
+	let mut front_states: Vec<Vec<Point>> = Vec::new();
+	let mut front_scores: Vec<Vec<f32>> = Vec::new();
+	for i in &fronts[0] {
+		if !front_scores.contains(&scores[*i]) {
+			front_states.push(pool[*i].clone());
+			front_scores.push(scores[*i].clone());
+		}
+	}
+	(front_states, front_scores)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_non_dominated_sort() {
+		let scores = vec![
+			vec![1., 5.], vec![2., 4.], vec![1., 4.], vec![0., 0.], vec![3., 1.]
+		];
+		let fronts = non_dominated_sort(&scores);
+		assert_eq!(fronts, vec![vec![0, 1, 4], vec![2], vec![3]]);
+	}
+
+	#[test]
+	fn test_crowding_distance() {
+		let scores = vec![vec![0., 4.], vec![1., 3.], vec![3., 1.], vec![4., 0.]];
+		let d = crowding_distance(&scores, &[0, 1, 2, 3]);
+		assert_eq!(d[0], f32::INFINITY);
+		assert_eq!(d[3], f32::INFINITY);
+		assert!((d[1] - 1.5).abs() < 1e-6);
+		assert!((d[2] - 1.5).abs() < 1e-6);
+	}
+
+	#[test]
+	fn test_nsga2() {
+		let mut conf = Configuration::new();
+		conf.w = 60.; conf.h = 100.;
+		conf.n = 3;
+		conf.counts = vec![1, 2];
+		conf.radius = vec![10., 20.];
+		let reference = init::random_state(&conf);
+
+		let mut params = Params::new();
+		params.size = 10; params.iters = 5;
+		params.cross_ratio = 0.9; params.mutate_ratio = 0.1;
+		let criteria = vec![Criterion::Coverage, Criterion::Displacement];
+		let (front, scores) = nsga2(&conf, &params, &criteria, &reference);
+
+		assert!(!front.is_empty());
+		assert_eq!(front.len(), scores.len());
+		for i in 0..scores.len() {
+			for j in 0..scores.len() {
+				assert!(!dominates(&scores[i], &scores[j]));
+				assert!(i == j || scores[i] != scores[j]);
+			}
+		}
+	}
+}
@@ -0,0 +1,152 @@
+use std::f32;
+
+use models::*;
+use models::point::Point;
+use super::hungarian::apply_hungarian;
+
+/// What a redeployment plan minimizes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Movement {
+	/// Sum of the distances travelled
+	Total,
+	/// Longest distance any sensor travels, then the sum
+	Bottleneck
+}
+
+/// One sensor travelling from its current position to a spot of the target layout
+#[derive(Debug, Clone, Copy)]
+pub struct Move {
+	pub sensor_type: usize,
+	/// Index of the sensor in the current layout
+	pub sensor: usize,
+	/// Index of the spot in the target layout
+	pub spot: usize,
+	pub from: Point,
+	pub to: Point,
+	pub distance: f32
+}
+
+#[derive(Debug, Clone)]
+pub struct Plan {
+	pub moves: Vec<Move>,
+	pub total: f32,
+	pub max: f32
+}
+
+/// Assigns the sensors of `current` to the spots of `target`, type by type,
+/// so that the chosen movement cost is minimal
+pub fn plan_redeployment(conf: &Configuration, current: &[Point], target: &[Point],
+		movement: Movement) -> Plan {
+	let mut moves: Vec<Move> = Vec::new();
+	let mut s = 0;
+	for t in 0..conf.counts.len() {
+		let count = conf.counts[t] as usize;
+		let spots: Vec<(Point, usize)> = (s..s + count).map(|i| (target[i], i)).collect();
+		let sensors: Vec<(Point, usize)> = (s..s + count).map(|i| (current[i], i)).collect();
+
+		let assigned = match movement {
+			Movement::Total => assign(&spots, &sensors, f32::INFINITY),
+			Movement::Bottleneck => bottleneck(&spots, &sensors)
+		};
+		for i in 0..count {
+			let (to, spot) = spots[i];
+			let (from, sensor) = assigned[i];
+			moves.push(Move {
+				sensor_type: t, sensor, spot,
+				from, to, distance: from.distance(&to)
+			});
+		}
+		s += count;
+	}
+
+	Plan {
+		total: moves.iter().map(|m| m.distance).sum(),
+		max: moves.iter().fold(0., |m, mv| f32::max(m, mv.distance)),
+		moves
+	}
+}
+
+/// Minimum total distance matching. Pairs further apart than `limit` are heavily penalized.
+/// Returns the sensor matched to each spot.
+fn assign(spots: &Vec<(Point, usize)>, sensors: &Vec<(Point, usize)>, limit: f32) -> Vec<(Point, usize)> {
+	let mut longest = 0.;
+	for a in spots {
+		for b in sensors {
+			longest = f32::max(longest, a.0.distance(&b.0));
+		}
+	}
+	let penalty = longest * (spots.len() + 1) as f32;
+
+	let mut g1 = spots.clone();
+	let mut g2 = sensors.clone();
+	let weight_fn = |a: &(Point, usize), b: &(Point, usize)| {
+		let d = a.0.distance(&b.0);
+		if d <= limit { -d } else { -d - penalty }
+	};
+	apply_hungarian(&mut g1, &mut g2, &weight_fn);
+	g2
+}
+
+/// Bisects over the candidate distances for the smallest longest move,
+/// then minimizes the total distance among matchings that respect it
+fn bottleneck(spots: &Vec<(Point, usize)>, sensors: &Vec<(Point, usize)>) -> Vec<(Point, usize)> {
+	let mut limits: Vec<f32> = Vec::new();
+	for a in spots {
+		for b in sensors {
+			limits.push(a.0.distance(&b.0));
+		}
+	}
+	limits.sort_by(|a, b| a.partial_cmp(b).unwrap());
+	limits.dedup();
+	if limits.is_empty() {
+		return sensors.clone();
+	}
+
+	let longest = |m: &Vec<(Point, usize)>| (0..m.len())
+		.fold(0., |acc, i| f32::max(acc, spots[i].0.distance(&m[i].0)));
+	let (mut lo, mut hi) = (0, limits.len() - 1);
+	let mut best = assign(spots, sensors, limits[hi]);
+	while lo < hi {
+		let mid = (lo + hi) / 2;
+		let m = assign(spots, sensors, limits[mid]);
+		if longest(&m) <= limits[mid] {
+			hi = mid;
+			best = m;
+		} else {
+			lo = mid + 1;
+		}
+	}
+
+	best
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_redeployment() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 3;
+		conf.counts = vec![2, 1];
+		conf.radius = vec![5., 10.];
+
+		let current = vec![Point{x: 0., y: 0.}, Point{x: -4., y: 3.}, Point{x: 50., y: 50.}];
+		let target = vec![Point{x: 0., y: 0.}, Point{x: 5., y: 0.}, Point{x: 50., y: 60.}];
+
+		// Staying put and sending the other sensor across is shortest in total
+		let plan = plan_redeployment(&conf, &current, &target, Movement::Total);
+		assert_eq!(plan.moves[0].sensor, 0);
+		assert!((plan.total - (90f32.sqrt() + 10.)).abs() < 1e-4);
+		assert_eq!(plan.moves[2].sensor, 2);
+		assert_eq!(plan.moves[2].sensor_type, 1);
+
+		// Swapping keeps every move within 10
+		let plan = plan_redeployment(&conf, &current, &target, Movement::Bottleneck);
+		assert_eq!(plan.moves[0].sensor, 1);
+		assert!((plan.max - 10.).abs() < 1e-4);
+		assert!((plan.total - 20.).abs() < 1e-4);
+		println!("{:?}", plan);
+	}
+}
@@ -0,0 +1,146 @@
+use models::*;
+use models::adaptive::coverage_fs;
+use models::point::Point;
+use super::*;
+
+/// How the sensor count is searched
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Search {
+	/// Tries 1, 2, 3, ... sensors
+	Incremental,
+	/// Bisects between 1 and the maximum count, assuming coverage grows with the count
+	Binary
+}
+
+/// Best layout found for one total sensor count
+#[derive(Debug, Clone)]
+pub struct CountResult {
+	pub n: i32,
+	pub counts: Vec<i32>,
+	pub coverage: f32,
+	pub state: Vec<Point>
+}
+
+/// Splits `total` sensors between the types in proportion to `mix`, largest remainders first
+pub fn counts_for(mix: &[f32], total: i32) -> Vec<i32> {
+	let sum: f32 = mix.iter().sum();
+	let shares: Vec<f32> = mix.iter().map(|m| m / sum * total as f32).collect();
+	let mut counts: Vec<i32> = shares.iter().map(|s| s.floor() as i32).collect();
+
+	let mut order: Vec<usize> = (0..mix.len()).collect();
+	order.sort_by(|a, b| (shares[*b] - shares[*b].floor())
+		.partial_cmp(&(shares[*a] - shares[*a].floor())).unwrap());
+	let missing = total - counts.iter().sum::<i32>();
+	for i in 0..missing as usize {
+		counts[order[i % order.len()]] += 1;
+	}
+
+	counts
+}
+
+/// Copy of `conf` with the given number of sensors per type
+pub fn with_counts(conf: &Configuration, counts: &[i32]) -> Configuration {
+	let mut c = conf.clone();
+	c.counts = counts.to_vec();
+	c.n = counts.iter().sum();
+	c
+}
+
+/// Runs the GA `runs` times with `n` sensors and keeps the layout with the best coverage
+pub fn best_of_runs(conf: &Configuration, params: &Params, mix: &[f32], n: i32, runs: i32) -> CountResult {
+	let counts = counts_for(mix, n);
+	let c = with_counts(conf, &counts);
+	let mut best = CountResult { n, counts, coverage: -1., state: Vec::new() };
+
+	for _ in 0..runs {
+		let state = genetic_algorithm(&c, params);
+		let coverage = coverage_fs(&c, &state);
+		if coverage > best.coverage {
+			best.coverage = coverage;
+			best.state = state;
+		}
+	}
+
+	best
+}
+
+/// Searches for the fewest sensors, split between types by `mix`, whose best run
+/// covers at least `target` of the field. Returns every count tried, ordered by count.
+pub fn min_sensors(conf: &Configuration, params: &Params, mix: &[f32], target: f32,
+		runs: i32, max_n: i32, search: Search) -> Vec<CountResult> {
+	let mut curve: Vec<CountResult> = Vec::new();
+
+	match search {
+		Search::Incremental => {
+			for n in 1..max_n+1 {
+				let result = best_of_runs(conf, params, mix, n, runs);
+				println!("{} sensors: coverage {}", n, result.coverage);
+				let reached = result.coverage >= target;
+				curve.push(result);
+				if reached {
+					break;
+				}
+			}
+		},
+		Search::Binary => {
+			let (mut lo, mut hi) = (1, max_n);
+			while lo <= hi {
+				let n = (lo + hi) / 2;
+				let result = best_of_runs(conf, params, mix, n, runs);
+				println!("{} sensors: coverage {}", n, result.coverage);
+				if result.coverage >= target {
+					hi = n - 1;
+				} else {
+					lo = n + 1;
+				}
+				curve.push(result);
+			}
+		}
+	}
+
+	curve.sort_by_key(|r| r.n);
+	curve
+}
+
+/// Smallest count in the curve that reaches the target
+pub fn smallest_reaching(curve: &[CountResult], target: f32) -> Option<&CountResult> {
+	curve.iter().find(|r| r.coverage >= target)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_counts_for() {
+		assert_eq!(counts_for(&[5., 5., 7.], 17), vec![5, 5, 7]);
+		assert_eq!(counts_for(&[1., 1.], 3).iter().sum::<i32>(), 3);
+		assert_eq!(counts_for(&[2., 1.], 6), vec![4, 2]);
+		assert_eq!(counts_for(&[1., 3.], 1), vec![0, 1]);
+	}
+
+	#[test]
+	fn test_min_sensors() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 1;
+		conf.counts = vec![1];
+		conf.radius = vec![20.];
+
+		let mut params = Params::new();
+		params.size = 10; params.iters = 10;
+		params.objective = Objective::Coverage;
+
+		// Each disk covers at most 12.6% of the field
+		let target = 0.3;
+		random::seed(3);
+		let curve = min_sensors(&conf, &params, &[1.], target, 1, 8, Search::Binary);
+		let best = smallest_reaching(&curve, target).expect("no count reached the target");
+		assert_eq!(best.state.len(), best.n as usize);
+
+		random::seed(3);
+		let incremental = min_sensors(&conf, &params, &[1.], target, 1, 8, Search::Incremental);
+		assert_eq!(best.n, incremental.last().unwrap().n);
+		assert_eq!(best.n, 3);
+	}
+}
@@ -1,16 +1,43 @@
 use quickersort::sort_by_key;
+use rand::Rng;
+use rand::distributions::{IndependentSample, Range};
 
 use models::*;
 use models::point::Point;
 
 pub fn best(adapt_fn: &Fn(&Configuration, &Vec<Point>)->f32, conf: &Configuration, 
 		states: &Vec<Vec<Point>>, size: usize) -> Vec<Vec<Point>> {
-	let mut new_states = states.clone();
-	// Convert float key to integer with 1e-5 accuracy
-	sort_by_key(&mut new_states, |s| (-1e5 * adapt_fn(conf, s)) as i64);
-	new_states = new_states[0..size].to_vec();
+	best_scored(adapt_fn, conf, states, size).0
+}
+
+/// Like `best`, also returning the score of each kept state
+pub fn best_scored(adapt_fn: &dyn Fn(&Configuration, &Vec<Point>)->f32, conf: &Configuration, 
+		states: &[Vec<Point>], size: usize) -> (Vec<Vec<Point>>, Vec<f32>) {
+	// Score each state once, converting the float key to integer with 1e-5 accuracy
+	let mut scored: Vec<(i64, f32, &Vec<Point>)> = states.iter()
+		.map(|s| {
+			let score = adapt_fn(conf, s);
+			((-1e5 * score) as i64, score, s)
+		})
+		.collect();
+	sort_by_key(&mut scored, |&(k, _, _)| k);
 
-	new_states
+	scored[0..size].iter()
+		.map(|&(_, score, s)| (s.clone(), score))
+		.unzip()
+}
+
+/// Index of the best of `k` randomly picked states
+pub fn tournament<R: Rng>(scores: &[f32], k: usize, r: &mut R) -> usize {
+	let pick = Range::new(0, scores.len());
+	let mut winner = pick.ind_sample(r);
+	for _ in 1..k {
+		let i = pick.ind_sample(r);
+		if scores[i] > scores[winner] {
+			winner = i;
+		}
+	}
+	winner
 }
 
 #[cfg(test)]
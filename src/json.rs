@@ -0,0 +1,78 @@
+/// Minimal writer for JSON objects
+#[derive(Debug, Clone)]
+pub struct JsonObject {
+	fields: Vec<String>
+}
+
+impl JsonObject {
+	pub fn new() -> JsonObject {
+		JsonObject { fields: Vec::new() }
+	}
+
+	/// Adds a field holding already formatted JSON
+	pub fn raw(&mut self, key: &str, value: String) -> &mut JsonObject {
+		self.fields.push(format!("\"{}\": {}", key, value));
+		self
+	}
+
+	pub fn num(&mut self, key: &str, value: f32) -> &mut JsonObject {
+		self.raw(key, number(value))
+	}
+
+	pub fn int(&mut self, key: &str, value: i64) -> &mut JsonObject {
+		self.raw(key, format!("{}", value))
+	}
+
+	pub fn opt_int(&mut self, key: &str, value: Option<usize>) -> &mut JsonObject {
+		match value {
+			Some(v) => self.int(key, v as i64),
+			None => self.raw(key, "null".to_string())
+		}
+	}
+
+	pub fn boolean(&mut self, key: &str, value: bool) -> &mut JsonObject {
+		self.raw(key, format!("{}", value))
+	}
+
+	pub fn string(&mut self, key: &str, value: &str) -> &mut JsonObject {
+		let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+		self.raw(key, format!("\"{}\"", escaped))
+	}
+
+	pub fn build(&self) -> String {
+		format!("{{{}}}", self.fields.join(", "))
+	}
+}
+
+/// Formats a number, writing null for values JSON cannot represent
+pub fn number(value: f32) -> String {
+	if value.is_finite() {
+		format!("{}", value)
+	} else {
+		"null".to_string()
+	}
+}
+
+pub fn array(items: &[String]) -> String {
+	format!("[{}]", items.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_object() {
+		let mut inner = JsonObject::new();
+		inner.num("x", 1.5).opt_int("parent", None);
+		let mut o = JsonObject::new();
+		o.string("name", "a \"b\"")
+			.int("n", 3)
+			.num("inf", f32::INFINITY)
+			.boolean("ok", true)
+			.raw("items", array(&[inner.build()]));
+
+		assert_eq!(o.build(),
+			"{\"name\": \"a \\\"b\\\"\", \"n\": 3, \"inf\": null, \"ok\": true, \"items\": [{\"x\": 1.5, \"parent\": null}]}");
+	}
+}
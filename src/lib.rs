@@ -0,0 +1,9 @@
+extern crate rand;
+extern crate quickersort;
+extern crate time;
+
+pub mod fileio;
+mod json;
+pub mod random;
+pub mod models;
+pub mod ga;
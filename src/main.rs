@@ -1,54 +1,174 @@
 #[macro_use]
 extern crate clap;
-extern crate rand;
-extern crate quickersort;
-extern crate time;
+extern crate area_cov;
 
-mod fileio;
-mod models;
-mod ga;
+use area_cov::{fileio, random, models, ga};
 
+use std::f32;
 use std::path::Path;
-use clap::App;
+use clap::{App, ArgMatches};
 
 use models::adaptive;
+use models::adaptive::Objective;
 use models::point::Point;
+use models::raster::*;
+use models::Configuration;
+use models::network::*;
+use models::sampling::*;
+use models::holes::*;
+use models::contribution::*;
+use models::failure::*;
+use ga::nsga::*;
+use ga::search::*;
+use ga::redeploy::*;
+use ga::island::*;
 
 fn main() {
     let yaml = load_yaml!("cli.yml");
 	let args = App::from_yaml(yaml).get_matches();
 
+	match args.subcommand() {
+		("evaluate", Some(sub)) => evaluate(sub),
+		("redeploy", Some(sub)) => redeploy(sub),
+		_ => optimize(&args)
+	}
+}
+
+fn optimize(args: &ArgMatches) {
 	let fname = Path::new(args.value_of("INPUT").unwrap());
-	let size = value_t!(args, "size", i32).unwrap();
-	let cross_ratio = value_t!(args, "cross_ratio", f32).unwrap();
-	let mutate_ratio = value_t!(args, "mutate_ratio", f32).unwrap();
-	let iters = value_t!(args, "iters", i32).unwrap();
 	let runs = value_t!(args, "runs", i32).unwrap();
+	let k = value_t!(args, "k", usize).unwrap();
+
+	let mut params = ga::Params::new();
+	params.size = value_t!(args, "size", i32).unwrap();
+	params.cross_ratio = value_t!(args, "cross_ratio", f32).unwrap();
+	params.mutate_ratio = value_t!(args, "mutate_ratio", f32).unwrap();
+	params.iters = value_t!(args, "iters", i32).unwrap();
+	params.objective = match args.value_of("objective").unwrap() {
+		"legacy-overlap" => Objective::LegacyOverlap,
+		"kcov" => Objective::KCoverage(k),
+		"coverage" => Objective::Coverage,
+		"hybrid" => Objective::Hybrid {
+			coverage_weight: value_t!(args, "coverage_weight", f32).unwrap(),
+			overlap_weight: value_t!(args, "overlap_weight", f32).unwrap()
+		},
+		"robust" => Objective::Robust {
+			failure: value_t!(args, "failure_prob", f32).unwrap(),
+			trials: trials_of(args)
+		},
+		_ => Objective::Overlap
+	};
+	params.connectivity = ga::Connectivity::from_name(args.value_of("connectivity").unwrap()).unwrap();
+	params.require_sink = args.is_present("require_sink");
+	params.budget = value_t!(args, "budget", f32).ok();
+	params.target = value_t!(args, "target_coverage", f32).ok();
+	params.mode = match args.value_of("mode").unwrap() {
+		"steady-state" => ga::Mode::SteadyState(match args.value_of("replacement").unwrap() {
+			"tournament" => match value_t!(args, "tournament_size", usize).unwrap() {
+				0 => panic!("--tournament-size must be at least 1"),
+				k => ga::Replacement::Tournament(k)
+			},
+			_ => ga::Replacement::Worst
+		}),
+		_ => ga::Mode::Generational
+	};
+	params.evaluations = value_t!(args, "evaluations", i32).ok();
+	if params.evaluations.is_some_and(|e| e <= params.size) {
+		panic!("--evaluations must exceed --size, the initial pool spends that many");
+	}
+	params.mating = ga::mating::Mating::from_name(args.value_of("mating").unwrap()).unwrap();
+	params.offspring = value_t!(args, "offspring", usize).ok();
+	params.diversity = match args.value_of("diversity").unwrap() {
+		"sharing" => ga::diversity::Diversity::Sharing(value_t!(args, "niche_radius", f32).unwrap()),
+		"crowding" => ga::diversity::Diversity::Crowding,
+		_ => ga::diversity::Diversity::None
+	};
+	params.dedup = value_t!(args, "dedup", f32).ok();
+	params.align = args.is_present("align");
+	params.realign_every = value_t!(args, "realign_every", i32).unwrap();
+	params.checkpoint = args.value_of("checkpoint").map(String::from);
+	params.checkpoint_every = value_t!(args, "checkpoint_every", i32).unwrap();
+
+	if let Ok(seed) = value_t!(args, "seed", u64) {
+		random::seed(seed);
+	}
+
+	// A resumed run keeps its own parameters
+	let checkpoint = args.value_of("resume").map(|c| {
+		let mut run = fileio::checkpoint_from_file(Path::new(c));
+		run.params.checkpoint = params.checkpoint.clone();
+		run.params.checkpoint_every = params.checkpoint_every;
+		run
+	});
+	if let Some(ref run) = checkpoint {
+		params = run.params.clone();
+	}
 	
 	eprintln!("Reading '{}'...", fname.to_str().unwrap());
-	let conf = fileio::config_from_file(&fname);
+	let mut conf = fileio::config_from_file(fname);
+	conf.variable_counts = params.budget.is_some() || params.target.is_some();
+	params.init = ga::init::Initializer::from_name(args.value_of("init").unwrap()).unwrap();
+	if let Some(seeds) = args.values_of("seed_layout") {
+		for s in seeds {
+			params.seeds.push(seed_layout(&conf, Path::new(s)));
+		}
+	}
+
+	// The exact overlap treats sectors as full disks
+	let overlap = matches!(params.objective, Objective::Overlap | Objective::Hybrid { .. });
+	if overlap && conf.sector_count() > 0 && !args.is_present("nsga2") {
+		panic!("The overlap and hybrid objectives do not support sector sensors");
+	}
+
+	if args.is_present("min_sensors") {
+		return min_sensor_search(args, &conf, &params);
+	}
+
+	if args.is_present("nsga2") {
+		if conf.variable_counts {
+			panic!("NSGA-II does not support variable sensor counts");
+		}
+		return pareto_front(args, &conf, &params);
+	}
+
+	let islands = islands_of(args, &params);
+	let sampling = sampling_of(args);
+	let mut best_conf = conf.clone();
 	let mut best_result: Vec<Point> = Vec::new();
 	let mut best_cov: f32 = 0.;
+	let mut best_rank = f32::MIN;
 
 	for r in 0..runs {
 		println!("Run {}/{}", r+1, runs);
 		let start = time::get_time();
-		let results = ga::genetic_algorithm(&conf, size, iters, 
-			cross_ratio, mutate_ratio);
+		let results = match checkpoint {
+			Some(ref run) if r == 0 => ga::resume(&conf, run.clone()),
+			_ if islands.params.len() > 1 => island_model(&conf, &islands),
+			_ => ga::genetic_algorithm(&conf, &params)
+		};
 		let elapsed = time::get_time() - start;
 		let m_el = elapsed.num_milliseconds();
-		let cov = adaptive::coverage_area(&conf, &results);
+		// Only the deployed sensors are evaluated and saved
+		let (run_conf, deployed) = conf.active_layout(&results);
+		let cov = adaptive::coverage_estimate(&run_conf, &deployed, &sampling);
+		let rank = if conf.variable_counts { params.score(&conf, &results) } else { cov.value };
 		
-		if cov > best_cov {
-			best_result = results;
-			best_cov = cov;
+		if rank > best_rank {
+			best_conf = run_conf.clone();
+			best_result = deployed;
+			best_cov = cov.value;
+			best_rank = rank;
 		}
 
 		eprintln!("-------------");
 		eprintln!("Elapsed time: {}ms", m_el);
-		eprintln!("Coverage area: {}/{}", cov, conf.h * conf.w);
+		eprintln!("Coverage area: {} ± {}/{}", cov.value, cov.error, conf.h * conf.w);
+		if conf.variable_counts {
+			eprintln!("Sensors per type: {:?}, cost {}", run_conf.counts, run_conf.total_cost());
+		}
 		eprintln!("-------------");
 	}
+	let conf = best_conf;
 
 	if let Some(s) = args.value_of("out") {
 		let oname = Path::new(&s);
@@ -59,6 +179,284 @@ fn main() {
 			Ok(_) => println!("Done.")
 		};
 	}
+	save_json(args, &conf, &best_result, best_cov);
 
 	println!("** Best coverage: {}/{} **", best_cov, conf.h * conf.w);
 }
+
+fn min_sensor_search(args: &ArgMatches, conf: &Configuration, params: &ga::Params) {
+	let target = value_t!(args, "min_sensors", f32).unwrap();
+	let runs = value_t!(args, "runs", i32).unwrap();
+	let max_n = value_t!(args, "max_sensors", i32).unwrap();
+	let mix: Vec<f32> = match args.values_of("mix") {
+		Some(v) => v.map(|m| m.parse::<f32>().expect("Failed to parse --mix")).collect(),
+		None => conf.counts.iter().map(|c| *c as f32).collect()
+	};
+	if mix.len() != conf.counts.len() {
+		panic!("--mix needs one share per sensor type, got {} for {} types", mix.len(), conf.counts.len());
+	}
+	let search = match args.value_of("search").unwrap() {
+		"incremental" => Search::Incremental,
+		_ => Search::Binary
+	};
+
+	let start = time::get_time();
+	let curve = min_sensors(conf, params, &mix, target, runs, max_n, search);
+	let elapsed = time::get_time() - start;
+
+	eprintln!("-------------");
+	eprintln!("Elapsed time: {}ms", elapsed.num_milliseconds());
+	eprintln!("{:>8} {:>16} {:>10}", "sensors", "per type", "coverage");
+	for r in &curve {
+		eprintln!("{:>8} {:>16} {:>10.4}", r.n, format!("{:?}", r.counts), r.coverage);
+	}
+	eprintln!("-------------");
+
+	let best = match smallest_reaching(&curve, target) {
+		Some(b) => b,
+		None => {
+			println!("** No count up to {} sensors covers {} of the field **", max_n, target);
+			return;
+		}
+	};
+	let best_conf = with_counts(conf, &best.counts);
+	let cov = adaptive::coverage_estimate(&best_conf, &best.state, &sampling_of(args));
+
+	if let Some(s) = args.value_of("out") {
+		let oname = Path::new(&s);
+		println!("Saving smallest layout to {}", s);
+		let stem = oname.file_stem().unwrap().to_str().unwrap();
+		let success = fileio::result_to_file(&best_conf, &best.state, cov.value, oname)
+			.and(fileio::curve_to_file(&curve, &oname.with_file_name(format!("{}-curve.csv", stem))));
+		match success {
+			Err(_) => panic!("Unable to save output"),
+			Ok(_) => println!("Done.")
+		};
+	}
+
+	println!("** Fewest sensors: {} {:?}, coverage {}/{} **", best.n, best.counts, cov.value, conf.h * conf.w);
+}
+
+fn pareto_front(args: &ArgMatches, conf: &Configuration, params: &ga::Params) {
+	let criteria: Vec<Criterion> = args.values_of("objectives").unwrap()
+		.map(|o| Criterion::from_name(o).unwrap())
+		.collect();
+	if criteria.contains(&Criterion::Overlap) && conf.sector_count() > 0 {
+		panic!("The overlap objective does not support sector sensors");
+	}
+	let reference = match args.value_of("reference") {
+		Some(r) => fileio::result_from_file(conf, Path::new(r)),
+		None => {
+			if criteria.contains(&Criterion::Displacement) || criteria.contains(&Criterion::MaxDisplacement) {
+				panic!("The displacement objective needs a --reference layout");
+			}
+			Vec::new()
+		}
+	};
+
+	let start = time::get_time();
+	let (front, scores) = nsga2(conf, params, &criteria, &reference);
+	let elapsed = time::get_time() - start;
+
+	eprintln!("-------------");
+	eprintln!("Elapsed time: {}ms", elapsed.num_milliseconds());
+	eprintln!("Pareto front: {} layouts", front.len());
+	for (i, s) in scores.iter().enumerate() {
+		let values: Vec<String> = criteria.iter().enumerate()
+			.map(|(k, c)| format!("{}={}", c.name(), c.measured(s[k])))
+			.collect();
+		eprintln!(" {}: {}", i, values.join(" "));
+	}
+	eprintln!("-------------");
+
+	if let Some(s) = args.value_of("out") {
+		println!("Saving Pareto front next to {}", s);
+		let success = fileio::front_to_files(conf, &front, &scores, &criteria, Path::new(&s));
+		match success {
+			Err(_) => panic!("Unable to save output"),
+			Ok(_) => println!("Done.")
+		};
+	}
+}
+
+fn evaluate(args: &ArgMatches) {
+	let fname = Path::new(args.value_of("INPUT").unwrap());
+	let rname = Path::new(args.value_of("RESULT").unwrap());
+	let k = value_t!(args, "k", usize).unwrap();
+
+	let conf = fileio::config_from_file(fname);
+	let conf = fileio::result_config_from_file(&conf, rname);
+	let result = fileio::result_from_file(&conf, rname);
+
+	let estimate = adaptive::coverage_estimate(&conf, &result, &sampling_of(args));
+	let cov = estimate.value;
+	println!("Coverage area: {} ± {}/{}", cov, estimate.error, conf.h * conf.w);
+	println!("Overlap area: {}", adaptive::overlap_area(&conf, &result));
+	println!("Legacy overlap index: {}", adaptive::overlap(&conf, &result));
+
+	let net = Network::from_state(&conf, &result);
+	println!("Network components: {}", net.component_count());
+	println!("Network diameter: {} hops", net.diameter());
+	if net.positions.len() > net.sensors {
+		let tree = RoutingTree::new(&net, routing_of(args));
+		let battery = value_t!(args, "battery", f32).unwrap();
+		println!("Sensors connected to a sink: {:.4}", net.connected_fraction(true));
+		println!("Maximum relay load: {}", tree.max_load());
+		println!("Estimated lifetime: {} rounds", tree.lifetime(battery));
+	}
+
+	let g = CoverageGrid::from_state(&conf, &result, DEFAULT_RESOLUTION);
+	let hist = g.depth_histogram(k);
+	println!("Covered fraction: {:.4}", g.coverage_fraction());
+	println!("Coverage depth:");
+	println!("{:>6} {:>10} {:>10}", "depth", "exactly", "at least");
+	for i in 0..k+1 {
+		let at_least: f32 = hist[i..].iter().sum();
+		let label = if i == k { format!("{}+", i) } else { format!("{}", i) };
+		println!("{:>6} {:>10.4} {:>10.4}", label, hist[i], at_least);
+	}
+
+	let circles = models::Circle::from_state(&conf, &result);
+	let contrib = marginal_contributions(&conf, &result);
+	println!("Sensor contributions:");
+	println!("{:>8} {:>8} {:>8} {:>6} {:>10} {:>10} {:>10} {:>9}",
+		"sensor", "x", "y", "radius", "exclusive", "overlap", "neighbours", "redundant");
+	for (i, c) in contrib.iter().enumerate() {
+		let label = if i < conf.n as usize { format!("{}", i) } else { format!("fixed {}", i - conf.n as usize) };
+		println!("{:>8} {:>8.2} {:>8.2} {:>6} {:>10.2} {:>10.2} {:>10} {:>9}", label,
+			circles[i].center.x, circles[i].center.y, circles[i].radius,
+			c.exclusive, c.overlap, c.neighbours, if c.redundant { "yes" } else { "" });
+	}
+	let redundant = contrib.iter().filter(|c| c.redundant).count();
+	println!("Redundant sensors: {}", redundant);
+
+	let trials = trials_of(args);
+	let mut models: Vec<(String, FailureModel)> = Vec::new();
+	if let Ok(p) = value_t!(args, "failure_prob", f32) {
+		models.push((format!("each sensor fails with p={}", p), FailureModel::Bernoulli(p)));
+	}
+	if let Ok(k) = value_t!(args, "failures", usize) {
+		models.push((format!("{} random sensors fail", k), FailureModel::Exactly(k)));
+	}
+	if let Ok(k) = value_t!(args, "worst_case", usize) {
+		models.push((format!("worst {} sensors fail", k), FailureModel::WorstCase(k)));
+	}
+	for (name, model) in models {
+		let rb = robustness(&conf, &result, model, trials);
+		println!("Robustness ({}):", name);
+		println!("  expected covered fraction {:.4}", rb.expected);
+		let pct: Vec<String> = [5., 25., 50., 75., 95.].iter()
+			.map(|q| format!("{}%: {:.4}", q, rb.percentile(*q)))
+			.collect();
+		println!("  percentiles {}", pct.join(", "));
+	}
+
+	let holes = find_holes(&conf, &g);
+	println!("Coverage holes: {}", holes.len());
+	if !holes.is_empty() {
+		println!("{:>4} {:>10} {:>23} {:>17} {:>8}", "hole", "area", "bounding box", "centroid", "radius");
+		for (i, h) in holes.iter().enumerate() {
+			let bbox = format!("({:.1},{:.1})-({:.1},{:.1})", h.min.x, h.min.y, h.max.x, h.max.y);
+			let centroid = format!("({:.1},{:.1})", h.centroid.x, h.centroid.y);
+			println!("{:>4} {:>10.2} {:>23} {:>17} {:>8.2}", i, h.area, bbox, centroid, h.radius);
+		}
+	}
+
+	save_json(args, &conf, &result, cov);
+}
+
+fn redeploy(args: &ArgMatches) {
+	let fname = Path::new(args.value_of("INPUT").unwrap());
+	let conf = fileio::config_from_file(fname);
+	let current = fileio::result_from_file(&conf, Path::new(args.value_of("CURRENT").unwrap()));
+	let target = fileio::result_from_file(&conf, Path::new(args.value_of("TARGET").unwrap()));
+	let movement = match args.value_of("movement").unwrap() {
+		"max" => Movement::Bottleneck,
+		_ => Movement::Total
+	};
+
+	let plan = plan_redeployment(&conf, &current, &target, movement);
+	println!("{:>4} {:>6} {:>6} {:>17} {:>17} {:>10}", "type", "sensor", "spot", "from", "to", "distance");
+	for m in &plan.moves {
+		println!("{:>4} {:>6} {:>6} {:>17} {:>17} {:>10.2}", m.sensor_type + 1, m.sensor, m.spot,
+			format!("({:.1},{:.1})", m.from.x, m.from.y), format!("({:.1},{:.1})", m.to.x, m.to.y), m.distance);
+	}
+	println!("Total travel: {}", plan.total);
+	println!("Longest travel: {}", plan.max);
+
+	if let Some(s) = args.value_of("out") {
+		println!("Saving movement plan to {}", s);
+		if fileio::plan_to_file(&plan, Path::new(&s)).is_err() {
+			panic!("Unable to save movement plan");
+		}
+	}
+}
+
+/// Reads a saved layout and maps it onto the genome of `conf`
+fn seed_layout(conf: &Configuration, path: &Path) -> Vec<Point> {
+	eprintln!("Seeding with '{}'...", path.to_str().unwrap());
+	let mut base = conf.clone();
+	base.variable_counts = false;
+	let saved_conf = fileio::result_config_from_file(&base, path);
+	let saved = fileio::result_from_file(&saved_conf, path);
+	ga::init::expand_layout(conf, &saved_conf, &saved)
+}
+
+/// Island model settings. Per-island ratios are repeated over the islands.
+fn islands_of(args: &ArgMatches, params: &ga::Params) -> Islands {
+	let m = value_t!(args, "islands", usize).unwrap_or(1);
+	let cross = values_t!(args, "island_cross_ratio", f32).unwrap_or(vec![params.cross_ratio]);
+	let mutate = values_t!(args, "island_mutate_ratio", f32).unwrap_or(vec![params.mutate_ratio]);
+	if m == 0 {
+		panic!("--islands must be at least 1");
+	}
+	let mut islands = Islands {
+		params: Vec::new(),
+		interval: value_t!(args, "migration_interval", i32).unwrap(),
+		migrants: value_t!(args, "migrants", usize).unwrap(),
+		topology: Topology::from_name(args.value_of("topology").unwrap()).unwrap()
+	};
+	if islands.interval <= 0 {
+		panic!("--migration-interval must be at least 1");
+	}
+	for i in 0..m {
+		let mut p = params.clone();
+		p.cross_ratio = cross[i % cross.len()];
+		p.mutate_ratio = mutate[i % mutate.len()];
+		islands.params.push(p);
+	}
+	islands
+}
+
+fn sampling_of(args: &ArgMatches) -> Sampling {
+	Sampling {
+		sampler: Sampler::from_name(args.value_of("sampler").unwrap()).unwrap(),
+		samples: value_t!(args, "samples", usize).unwrap()
+	}
+}
+
+fn trials_of(args: &ArgMatches) -> usize {
+	let trials = value_t!(args, "trials", usize).unwrap();
+	if trials == 0 {
+		panic!("--trials must be at least 1");
+	}
+	trials
+}
+
+fn routing_of(args: &ArgMatches) -> Routing {
+	match args.value_of("routing").unwrap() {
+		"distance" => Routing::ShortestPath,
+		_ => Routing::MinHop
+	}
+}
+
+fn save_json(args: &ArgMatches, conf: &Configuration, result: &Vec<Point>, cov: f32) {
+	if let Some(s) = args.value_of("json") {
+		let battery = value_t!(args, "battery", f32).unwrap();
+		println!("Saving JSON result to {}", s);
+		let success = fileio::result_to_json(conf, result, cov, routing_of(args), battery, Path::new(&s));
+		if success.is_err() {
+			panic!("Unable to save JSON output");
+		}
+	}
+}
@@ -1,35 +1,43 @@
 use std::f32;
-use ga::init::random_points;
+use models::sensing::detection_probability_among;
+use models::index::GridIndex;
+use models::sampling::*;
+use models::failure::*;
+use models::raster::*;
+use models::geometry::*;
 
 use super::*;
 
-/// Calculates coverage area using Monte Carlo method
+/// Calculates coverage area with the default sampling, see `coverage_estimate`
 pub fn coverage_area(conf: &Configuration, state: &Vec<Point>) -> f32 {
-	let l = 1000000.;
-	let a_s = conf.h * conf.w / l;
-	let mut total = 0.0;
+	coverage_estimate(conf, state, &Sampling::new()).value
+}
 
+/// Estimates the coverage area by sampling the field in `REPLICATES` independent batches.
+/// A point counts as covered when its detection probability reaches `conf.threshold`.
+/// The error is the standard error of the mean over the batches.
+pub fn coverage_estimate(conf: &Configuration, state: &Vec<Point>, sampling: &Sampling) -> Estimate {
 	let circles: Vec<Circle> = Circle::from_state(conf, state);
+	let index = GridIndex::new(conf, &circles);
+	let per_batch = usize::max(1, sampling.samples / REPLICATES);
 
-	let points = random_points(conf, l as i32);
-	for p in points {
-		let mut covered = false;
-		for j in 0..circles.len() {
-			let c = &circles[j];
-			if c.contains(&p) {
-				covered = true;
-				break;
+	let mut areas = Vec::new();
+	for _ in 0..REPLICATES {
+		let points = sampling.sampler.points(conf, per_batch);
+		let mut total = 0.;
+		for p in &points {
+			if detection_probability_among(&circles, index.candidates(p), p) >= conf.threshold {
+				total += 1.;
 			}
 		}
-		if covered {
-			total += 1.;
-		}
+		areas.push((conf.h * conf.w) as f64 * total / points.len() as f64);
 	}
 
-	a_s * total
+	Estimate::from_replicates(&areas)
 }
 
-/// Calculates overlap index
+/// Calculates the legacy overlap index. It visits every pair twice and
+/// weighs intersections with heuristic factors instead of measuring area.
 pub fn overlap(conf: &Configuration, state: &Vec<Point>) -> f32 {
 	let mut ov = 0.;
 	let circles = Circle::from_state(conf, state);
@@ -89,6 +97,106 @@ pub fn overlap_fs(conf: &Configuration, state: &Vec<Point>) -> f32 {
 	}
 }
 
+/// Exact overlap: total lens area of all pairs of sensors plus the
+/// sensing area that lies outside the field. Sectors are treated as full disks.
+pub fn overlap_area(conf: &Configuration, state: &Vec<Point>) -> f32 {
+	let circles = Circle::from_state(conf, state);
+	let mut ov = 0.;
+
+	let index = GridIndex::new(conf, &circles);
+
+	for i in 0..circles.len() {
+		for j in index.neighbours(&circles, i) {
+			if j > i {
+				ov += lens_area(&circles[i], &circles[j]);
+			}
+		}
+		ov += outside_area(conf, &circles[i]);
+	}
+
+	ov
+}
+
+/// Area of `c` that lies outside the field
+pub fn outside_area(conf: &Configuration, c: &Circle) -> f32 {
+	let inside = disk_rect_area(c, 0., conf.w, 0., conf.h);
+	f32::max(0., f32::consts::PI * c.radius * c.radius - inside)
+}
+
+pub fn overlap_area_fs(conf: &Configuration, state: &Vec<Point>) -> f32 {
+	let ov = overlap_area(conf, state);
+	if ov == 0. {
+		f32::MAX
+	} else {
+		1. / ov
+	}
+}
+
+/// Fraction of the field covered at least 1, 2, ..., k times
+pub fn k_coverage(conf: &Configuration, state: &Vec<Point>, k: usize) -> Vec<f32> {
+	let g = CoverageGrid::from_state(conf, state, DEFAULT_RESOLUTION);
+	let hist = g.depth_histogram(k);
+
+	let mut fractions = vec![0.; k];
+	let mut acc = 0.;
+	for i in (1..k+1).rev() {
+		acc += hist[i];
+		fractions[i - 1] = acc;
+	}
+	fractions
+}
+
+/// Mean coverage depth capped at k, scaled to [0, 1]. Reaches 1 when the whole field is k-covered.
+pub fn k_coverage_fs(conf: &Configuration, state: &Vec<Point>, k: usize) -> f32 {
+	let fractions = k_coverage(conf, state, k);
+	fractions.iter().sum::<f32>() / k as f32
+}
+
+/// Fraction of the field covered, evaluated on the coverage raster
+pub fn coverage_fs(conf: &Configuration, state: &Vec<Point>) -> f32 {
+	CoverageGrid::from_state(conf, state, DEFAULT_RESOLUTION).coverage_fraction()
+}
+
+/// Weighted coverage fraction minus the overlap area relative to the field area
+pub fn hybrid_fs(conf: &Configuration, state: &Vec<Point>, coverage_weight: f32, overlap_weight: f32) -> f32 {
+	let ov = overlap_area(conf, state) / (conf.w * conf.h);
+	coverage_weight * coverage_fs(conf, state) - overlap_weight * ov
+}
+
+/// Fitness function optimized by the GA
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+	/// Inverse of the overlap area, see `overlap_area`
+	Overlap,
+	/// Inverse of the legacy overlap index
+	LegacyOverlap,
+	/// Capped coverage depth, see `k_coverage_fs`
+	KCoverage(usize),
+	/// Covered fraction of the field
+	Coverage,
+	/// Coverage with an overlap penalty, see `hybrid_fs`
+	Hybrid { coverage_weight: f32, overlap_weight: f32 },
+	/// Expected covered fraction when each sensor fails with probability `failure`
+	Robust { failure: f32, trials: usize }
+}
+
+impl Objective {
+	pub fn score(&self, conf: &Configuration, state: &Vec<Point>) -> f32 {
+		match *self {
+			Objective::Overlap => overlap_area_fs(conf, state),
+			Objective::LegacyOverlap => overlap_fs(conf, state),
+			Objective::KCoverage(k) => k_coverage_fs(conf, state, k),
+			Objective::Coverage => coverage_fs(conf, state),
+			Objective::Hybrid { coverage_weight, overlap_weight } => {
+				hybrid_fs(conf, state, coverage_weight, overlap_weight)
+			},
+			Objective::Robust { failure, trials } => {
+				robustness(conf, state, FailureModel::Bernoulli(failure), trials).expected
+			}
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -99,7 +207,8 @@ mod tests {
 		let conf = Configuration {
 			w: 20., h: 50., n: 3,
 			counts: vec![1, 2],
-			radius: vec![10., 20.]
+			radius: vec![10., 20.],
+			..Configuration::new()
 		};
 		let state = &random_init(&conf, 1)[0];
 		println!("{:?}", state);
@@ -113,7 +222,8 @@ mod tests {
 		let conf = Configuration {
 			w: 20., h: 50., n: 3,
 			counts: vec![1, 2],
-			radius: vec![10., 20.]
+			radius: vec![10., 20.],
+			..Configuration::new()
 		};
 		let state = &random_init(&conf, 1)[0];
 		println!("{:?}", state);
@@ -124,4 +234,86 @@ mod tests {
 		let fs = overlap_fs(&conf, &state);
 		println!("{:?}", fs);
 	}
+
+	#[test]
+	fn test_overlap_area() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 3;
+		conf.counts = vec![3];
+		conf.radius = vec![10.];
+		let state = vec![Point{x: 20., y: 50.}, Point{x: 35., y: 50.}, Point{x: 0., y: 80.}];
+
+		// One lens between the first two, half of the third disk outside the field
+		let c = Circle::from_state(&conf, &state);
+		let expected = lens_area(&c[0], &c[1]) + 50. * f32::consts::PI;
+		assert!((overlap_area(&conf, &state) - expected).abs() < 1e-3);
+	}
+
+	/// Documents how the legacy index relates to the exact overlap area
+	#[test]
+	fn test_legacy_overlap() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 2;
+		conf.counts = vec![1, 1];
+		conf.radius = vec![20., 10.];
+		let at = |d: f32| vec![Point{x: 40., y: 50.}, Point{x: 40. + d, y: 50.}];
+
+		// Both vanish for disjoint disks inside the field
+		assert_eq!(overlap(&conf, &at(35.)), 0.);
+		assert_eq!(overlap_area(&conf, &at(35.)), 0.);
+
+		// For partial overlaps the index grows linearly with the depth r1 + r2 - d,
+		// counted once from each circle, while the area grows faster than linearly
+		for &d in &[25., 20., 15.] {
+			let depth = 30. - d;
+			let gamma = (30. * 10.) / (20. * 20.);
+			assert!((overlap(&conf, &at(d)) - 2. * gamma * depth).abs() < 1e-3);
+		}
+		let a1 = overlap_area(&conf, &at(25.));
+		let a2 = overlap_area(&conf, &at(20.));
+		let a3 = overlap_area(&conf, &at(15.));
+		assert!(a1 > 0. && a2 - a1 > a1 && a3 - a2 > 0.);
+
+		// Once a disk is nested the area saturates at the smaller disk,
+		// while the index uses a constant beta * r2 for each ordered pair
+		let nested = overlap_area(&conf, &at(5.));
+		assert!((nested - 100. * f32::consts::PI).abs() < 1e-3);
+		assert_eq!(overlap(&conf, &at(5.)), overlap(&conf, &at(2.)));
+	}
+
+	#[test]
+	fn test_k_coverage() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 3;
+		conf.counts = vec![3];
+		conf.radius = vec![20.];
+		let state = vec![Point::wrap(50.); 3];
+
+		let kc = k_coverage(&conf, &state, 4);
+		println!("{:?}", kc);
+		assert_eq!(kc.len(), 4);
+		assert!(kc[0] > 0.12 && kc[0] < 0.13);
+		assert_eq!(kc[0], kc[2]);
+		assert_eq!(kc[3], 0.);
+		assert!((k_coverage_fs(&conf, &state, 4) - 0.75 * kc[0]).abs() < 1e-6);
+	}
+
+	#[test]
+	fn test_hybrid() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 2;
+		conf.counts = vec![2];
+		conf.radius = vec![20.];
+		let apart = vec![Point{x: 25., y: 50.}, Point{x: 75., y: 50.}];
+		let close = vec![Point{x: 40., y: 50.}, Point{x: 60., y: 50.}];
+
+		assert!(coverage_fs(&conf, &apart) > coverage_fs(&conf, &close));
+		assert_eq!(hybrid_fs(&conf, &apart, 1., 1.), coverage_fs(&conf, &apart));
+		assert!(hybrid_fs(&conf, &close, 1., 1.) < coverage_fs(&conf, &close));
+		assert_eq!(Objective::Coverage.score(&conf, &apart), coverage_fs(&conf, &apart));
+	}
 }
@@ -0,0 +1,87 @@
+use models::raster::*;
+use models::index::GridIndex;
+use models::geometry::lens_area;
+
+use super::*;
+
+/// What a single sensor adds to a layout
+#[derive(Debug, Clone, Copy)]
+pub struct Contribution {
+	/// Area that only this sensor covers, i.e. the coverage lost if it were removed
+	pub exclusive: f32,
+	/// Total lens area shared with other sensors
+	pub overlap: f32,
+	/// Number of sensors whose disks intersect this one
+	pub neighbours: usize,
+	/// The sensor covers nothing on its own
+	pub redundant: bool
+}
+
+/// Marginal contribution of every sensor, in the order of `Circle::from_state`
+pub fn marginal_contributions(conf: &Configuration, state: &Vec<Point>) -> Vec<Contribution> {
+	let circles = Circle::from_state(conf, state);
+	let index = GridIndex::new(conf, &circles);
+	let mut g = CoverageGrid::from_state(conf, state, DEFAULT_RESOLUTION);
+	let cell_area = conf.w * conf.h / g.miss.len() as f32;
+
+	let mut result = Vec::new();
+	for i in 0..circles.len() {
+		let lost = -g.remove(&circles[i]);
+		g.add(&circles[i]);
+
+		let mut overlap = 0.;
+		let mut neighbours = 0;
+		for j in index.neighbours(&circles, i) {
+			let lens = lens_area(&circles[i], &circles[j]);
+			if lens > 0. {
+				overlap += lens;
+				neighbours += 1;
+			}
+		}
+
+		result.push(Contribution {
+			exclusive: lost as f32 * cell_area,
+			overlap,
+			neighbours,
+			redundant: lost == 0
+		});
+	}
+
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::f32;
+
+	#[test]
+	fn test_contributions() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 4;
+		conf.counts = vec![3, 1];
+		conf.radius = vec![10., 5.];
+		// Two partly overlapping disks, one far away and a small one inside the first
+		let state = vec![
+			Point{x: 30., y: 50.}, Point{x: 45., y: 50.},
+			Point{x: 80., y: 20.}, Point{x: 28., y: 50.}
+		];
+
+		let contrib = marginal_contributions(&conf, &state);
+		for c in &contrib {
+			println!("{:?}", c);
+		}
+
+		let disk = 100. * f32::consts::PI;
+		let circles = Circle::from_state(&conf, &state);
+		let lens = lens_area(&circles[0], &circles[1]);
+		assert!((contrib[2].exclusive - disk).abs() / disk < 0.02);
+		assert_eq!(contrib[2].neighbours, 0);
+		assert!((contrib[1].exclusive - (disk - lens)).abs() / disk < 0.02);
+		assert!((contrib[1].overlap - lens).abs() < 1e-3);
+		assert_eq!(contrib[0].neighbours, 2);
+		assert!(contrib[3].redundant);
+		assert!(!contrib[0].redundant);
+	}
+}
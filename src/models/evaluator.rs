@@ -0,0 +1,191 @@
+use models::raster::*;
+use models::geometry::lens_area;
+use models::adaptive::outside_area;
+use models::index::GridIndex;
+
+use super::*;
+
+/// Change caused by moving one sensor
+#[derive(Debug, Clone, Copy)]
+pub struct Delta {
+	pub sensor: usize,
+	pub from: Point,
+	pub to: Point,
+	/// Change of the covered fraction of the field
+	pub coverage: f32,
+	/// Change of the overlap area, see `adaptive::overlap_area`
+	pub overlap: f32
+}
+
+/// Keeps a coverage raster and the overlap area of a layout up to date while
+/// single sensors move, so each move costs only the cells the sensor reaches
+/// and the sensors near its old and new position.
+pub struct Evaluator<'a> {
+	conf: &'a Configuration,
+	state: Vec<Point>,
+	circles: Vec<Circle>,
+	grid: CoverageGrid,
+	index: GridIndex,
+	covered: usize,
+	overlap: f64,
+	undo: Vec<(Delta, usize, f64)>
+}
+
+impl<'a> Evaluator<'a> {
+	pub fn new(conf: &'a Configuration, state: &Vec<Point>) -> Evaluator<'a> {
+		Evaluator::with_resolution(conf, state, DEFAULT_RESOLUTION)
+	}
+
+	pub fn with_resolution(conf: &'a Configuration, state: &Vec<Point>, resolution: usize) -> Evaluator<'a> {
+		let circles = Circle::from_state(conf, state);
+		let grid = CoverageGrid::from_state(conf, state, resolution);
+		let covered = grid.covered_cells();
+		let index = GridIndex::new(conf, &circles);
+
+		let mut overlap = 0.;
+		for i in 0..circles.len() {
+			overlap += outside_area(conf, &circles[i]) as f64;
+			for j in index.neighbours(&circles, i) {
+				if j > i {
+					overlap += lens_area(&circles[i], &circles[j]) as f64;
+				}
+			}
+		}
+
+		Evaluator {
+			conf,
+			state: state.clone(),
+			circles,
+			grid,
+			index,
+			covered,
+			overlap,
+			undo: Vec::new()
+		}
+	}
+
+	pub fn state(&self) -> &Vec<Point> {
+		&self.state
+	}
+
+	/// Covered fraction of the field
+	pub fn coverage(&self) -> f32 {
+		self.covered as f32 / self.grid.miss.len() as f32
+	}
+
+	pub fn overlap(&self) -> f32 {
+		self.overlap as f32
+	}
+
+	/// Moves sensor `i` to `to`. The move can be undone with `rollback` until `commit` is called.
+	pub fn move_sensor(&mut self, i: usize, to: Point) -> Delta {
+		let from = self.state[i];
+		let before = (self.covered, self.overlap);
+
+		let diff = self.relocate(i, to);
+		let delta = Delta {
+			sensor: i, from, to,
+			coverage: diff.0 as f32 / self.grid.miss.len() as f32,
+			overlap: diff.1 as f32
+		};
+		self.undo.push((delta, before.0, before.1));
+
+		delta
+	}
+
+	/// Undoes the latest move that was not committed
+	pub fn rollback(&mut self) -> Option<Delta> {
+		match self.undo.pop() {
+			Some((delta, covered, overlap)) => {
+				self.relocate(delta.sensor, delta.from);
+				// Restore the totals exactly instead of accumulating rounding errors
+				self.covered = covered;
+				self.overlap = overlap;
+				Some(delta)
+			},
+			None => None
+		}
+	}
+
+	/// Accepts all moves made so far
+	pub fn commit(&mut self) {
+		self.undo.clear();
+	}
+
+	fn relocate(&mut self, i: usize, to: Point) -> (isize, f64) {
+		let mut ov = -self.contribution(i);
+		let mut cov = self.grid.remove(&self.circles[i]);
+		self.index.remove(i, &self.circles[i]);
+
+		self.state[i] = to;
+		self.circles[i].center = to;
+
+		cov += self.grid.add(&self.circles[i]);
+		self.index.insert(i, &self.circles[i]);
+		ov += self.contribution(i);
+
+		self.covered = (self.covered as isize + cov) as usize;
+		self.overlap += ov;
+		(cov, ov)
+	}
+
+	/// Overlap area involving sensor `i`
+	fn contribution(&self, i: usize) -> f64 {
+		let c = &self.circles[i];
+		let mut ov = outside_area(self.conf, c) as f64;
+		for j in self.index.neighbours(&self.circles, i) {
+			ov += lens_area(c, &self.circles[j]) as f64;
+		}
+		ov
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ga::init::{random_init, random_points};
+	use models::adaptive::*;
+
+	#[test]
+	fn test_move_sensor() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 30;
+		conf.counts = vec![20, 10];
+		conf.radius = vec![8., 15.];
+		let state = random_init(&conf, 1).pop().unwrap();
+
+		// The overlap area is summed in a different order, allow for rounding
+		let close = |a: f32, b: f32| (a - b).abs() <= 1e-4 * f32::max(1., b);
+
+		let mut ev = Evaluator::new(&conf, &state);
+		assert_eq!(ev.coverage(), coverage_fs(&conf, &state));
+		assert!(close(ev.overlap(), overlap_area(&conf, &state)));
+
+		let targets = random_points(&conf, 50);
+		for (k, p) in targets.iter().enumerate() {
+			let before = (ev.coverage(), ev.overlap());
+			let delta = ev.move_sensor(k % conf.n as usize, *p);
+			assert!((ev.coverage() - before.0 - delta.coverage).abs() < 1e-6);
+			assert!(close(ev.overlap(), before.1 + delta.overlap));
+
+			let fresh = ev.state().clone();
+			assert!((ev.coverage() - coverage_fs(&conf, &fresh)).abs() < 1e-6);
+			assert!(close(ev.overlap(), overlap_area(&conf, &fresh)));
+		}
+		println!("coverage {}, overlap {}", ev.coverage(), ev.overlap());
+
+		// Rolling back returns to the committed layout
+		ev.commit();
+		let committed = (ev.state().clone(), ev.coverage(), ev.overlap());
+		ev.move_sensor(0, Point::wrap(50.));
+		ev.move_sensor(1, Point::wrap(50.));
+		assert!(ev.rollback().is_some());
+		assert!(ev.rollback().is_some());
+		assert!(ev.rollback().is_none());
+		assert!(Point::all_equal(ev.state(), &committed.0));
+		assert_eq!(ev.coverage(), committed.1);
+		assert_eq!(ev.overlap(), committed.2);
+		assert_eq!(ev.coverage(), coverage_fs(&conf, &committed.0));
+	}
+}
@@ -0,0 +1,150 @@
+use rand::distributions::{Range, IndependentSample};
+use rand;
+use random;
+
+use models::raster::*;
+use super::*;
+
+/// Which sensors stop working
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailureModel {
+	/// Each sensor fails independently with the given probability
+	Bernoulli(f32),
+	/// A uniformly random set of exactly k sensors fails
+	Exactly(usize),
+	/// The k sensors whose loss hurts most, chosen greedily one at a time
+	WorstCase(usize)
+}
+
+/// Covered fractions of the field after failures
+#[derive(Debug, Clone)]
+pub struct Robustness {
+	pub expected: f32,
+	/// Covered fraction in each trial, sorted ascending
+	pub trials: Vec<f32>
+}
+
+impl Robustness {
+	/// Covered fraction that `q` percent of the trials do not exceed
+	pub fn percentile(&self, q: f32) -> f32 {
+		let last = self.trials.len() - 1;
+		let i = (q / 100. * last as f32).round() as usize;
+		self.trials[usize::min(i, last)]
+	}
+}
+
+/// Simulates sensor failures on the coverage raster. The worst case is deterministic
+/// and runs once, the random models run `trials` times.
+pub fn robustness(conf: &Configuration, state: &Vec<Point>, model: FailureModel, trials: usize) -> Robustness {
+	match model {
+		FailureModel::WorstCase(_) => {},
+		_ => assert!(trials >= 1, "Random failure models need at least one trial")
+	}
+	let circles = Circle::from_state(conf, state);
+	let mut g = CoverageGrid::from_state(conf, state, DEFAULT_RESOLUTION);
+	let total = g.miss.len() as f32;
+	let covered = g.covered_cells() as isize;
+
+	let mut r = random::thread_rng();
+	let unit = Range::new(0., 1.);
+	let mut values: Vec<f32> = Vec::new();
+
+	match model {
+		FailureModel::WorstCase(k) => {
+			let mut alive: Vec<usize> = (0..circles.len()).collect();
+			let mut lost = 0;
+			for _ in 0..usize::min(k, circles.len()) {
+				// Remove the sensor whose loss uncovers the most cells
+				let mut worst = (0, isize::MAX);
+				for (a, i) in alive.iter().enumerate() {
+					let delta = g.remove(&circles[*i]);
+					g.add(&circles[*i]);
+					if delta < worst.1 {
+						worst = (a, delta);
+					}
+				}
+				let i = alive.remove(worst.0);
+				lost += g.remove(&circles[i]);
+			}
+			values.push((covered + lost) as f32 / total);
+		},
+		_ => {
+			for _ in 0..trials {
+				let failed: Vec<usize> = match model {
+					FailureModel::Bernoulli(p) => (0..circles.len())
+						.filter(|_| unit.ind_sample(&mut r) < p)
+						.collect(),
+					FailureModel::Exactly(k) => {
+						let k = usize::min(k, circles.len());
+						rand::seq::sample_iter(&mut r, 0..circles.len(), k).unwrap()
+					},
+					FailureModel::WorstCase(_) => unreachable!()
+				};
+
+				let mut lost = 0;
+				for i in &failed {
+					lost += g.remove(&circles[*i]);
+				}
+				values.push((covered + lost) as f32 / total);
+				for i in &failed {
+					g.add(&circles[*i]);
+				}
+			}
+		}
+	}
+
+	values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+	Robustness {
+		expected: values.iter().sum::<f32>() / values.len() as f32,
+		trials: values
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use models::adaptive::coverage_fs;
+
+	fn instance() -> (Configuration, Vec<Point>) {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 4;
+		conf.counts = vec![3, 1];
+		conf.radius = vec![10., 20.];
+		let state = vec![
+			Point{x: 20., y: 20.}, Point{x: 80., y: 20.},
+			Point{x: 20., y: 80.}, Point{x: 60., y: 60.}
+		];
+		(conf, state)
+	}
+
+	#[test]
+	fn test_failures() {
+		let (conf, state) = instance();
+		let full = coverage_fs(&conf, &state);
+
+		let none = robustness(&conf, &state, FailureModel::Bernoulli(0.), 10);
+		assert_eq!(none.expected, full);
+		assert_eq!(none.percentile(5.), full);
+
+		let all = robustness(&conf, &state, FailureModel::Exactly(4), 10);
+		assert_eq!(all.expected, 0.);
+
+		// Losing the large sensor is the worst single failure
+		let worst = robustness(&conf, &state, FailureModel::WorstCase(1), 0);
+		let mut moved = state.clone();
+		moved[3] = Point::wrap(-100.);
+		let without = coverage_fs(&conf, &moved);
+		assert_eq!(worst.trials.len(), 1);
+		assert!((worst.expected - without).abs() < 1e-6);
+
+		let one = robustness(&conf, &state, FailureModel::Exactly(1), 200);
+		println!("{} {} {}", one.expected, one.percentile(5.), one.percentile(95.));
+		assert!(one.percentile(0.) >= worst.expected);
+		assert!(one.expected < full && one.expected > worst.expected);
+
+		let half = robustness(&conf, &state, FailureModel::Bernoulli(0.5), 200);
+		assert!(half.percentile(5.) <= half.percentile(50.));
+		assert!(half.percentile(50.) <= half.percentile(95.));
+	}
+}
@@ -0,0 +1,110 @@
+use std::f64::consts;
+
+use super::*;
+
+/// Area of the intersection of two disks
+pub fn lens_area(c1: &Circle, c2: &Circle) -> f32 {
+	let (r1, r2) = (c1.radius as f64, c2.radius as f64);
+	let d = c1.center.distance(&c2.center) as f64;
+	if d >= r1 + r2 {
+		return 0.;
+	}
+	if d <= (r1 - r2).abs() {
+		let r = f64::min(r1, r2);
+		return (consts::PI * r * r) as f32;
+	}
+
+	let a1 = ((d * d + r1 * r1 - r2 * r2) / (2. * d * r1)).acos();
+	let a2 = ((d * d + r2 * r2 - r1 * r1) / (2. * d * r2)).acos();
+	let k = ((-d + r1 + r2) * (d + r1 - r2) * (d - r1 + r2) * (d + r1 + r2)).sqrt();
+	(r1 * r1 * a1 + r2 * r2 * a2 - 0.5 * k) as f32
+}
+
+/// Area of the intersection of a disk and the rectangle [x0, x1] x [y0, y1].
+/// Integrates the vertical chord length exactly, piece by piece.
+pub fn disk_rect_area(c: &Circle, x0: f32, x1: f32, y0: f32, y1: f32) -> f32 {
+	let (cx, cy, r) = (c.center.x as f64, c.center.y as f64, c.radius as f64);
+	// Work relative to the center
+	let (x0, x1) = (f64::max(x0 as f64 - cx, -r), f64::min(x1 as f64 - cx, r));
+	let (y0, y1) = (y0 as f64 - cy, y1 as f64 - cy);
+	if x0 >= x1 || y0 >= y1 {
+		return 0.;
+	}
+
+	// Half chord h(x) = sqrt(r^2 - x^2) and its antiderivative
+	let half = |x: f64| f64::max(0., r * r - x * x).sqrt();
+	let integral = |x: f64| {
+		let x = f64::min(f64::max(x, -r), r);
+		0.5 * (x * half(x) + r * r * (x / r).asin())
+	};
+
+	// Break where the chord ends cross y0 or y1
+	let mut breaks = vec![x0, x1];
+	for y in &[y0, y1] {
+		if y.abs() < r {
+			let x = (r * r - y * y).sqrt();
+			breaks.push(-x);
+			breaks.push(x);
+		}
+	}
+	breaks.retain(|x| *x >= x0 && *x <= x1);
+	breaks.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+	let mut area = 0.;
+	for w in breaks.windows(2) {
+		let (a, b) = (w[0], w[1]);
+		if b <= a {
+			continue;
+		}
+		let m = 0.5 * (a + b);
+		let h = half(m);
+		// Chord [-h, h] clipped to [y0, y1]: each end is either the circle or a constant
+		let top_clipped = h > y1;
+		let bottom_clipped = -h < y0;
+		if (if top_clipped { y1 } else { h }) <= (if bottom_clipped { y0 } else { -h }) {
+			continue;
+		}
+		let circle_part = integral(b) - integral(a);
+		let top = if top_clipped { y1 * (b - a) } else { circle_part };
+		let bottom = if bottom_clipped { y0 * (b - a) } else { -circle_part };
+		area += top - bottom;
+	}
+
+	area as f32
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::f32;
+
+	#[test]
+	fn test_lens_area() {
+		let c1 = Circle::new(Point{x: 0., y: 0.}, 20.);
+		let c2 = Circle::new(Point{x: 20., y: 0.}, 20.);
+		let expected = 2. * 400. * (0.5f32).acos() - 10. * (1200f32).sqrt();
+		assert!((lens_area(&c1, &c2) - expected).abs() < 1e-3);
+		assert_eq!(lens_area(&c1, &c2), lens_area(&c2, &c1));
+
+		let far = Circle::new(Point{x: 50., y: 0.}, 20.);
+		assert_eq!(lens_area(&c1, &far), 0.);
+		let inner = Circle::new(Point{x: 5., y: 0.}, 10.);
+		assert!((lens_area(&c1, &inner) - 100. * f32::consts::PI).abs() < 1e-3);
+	}
+
+	#[test]
+	fn test_disk_rect_area() {
+		let full = f32::consts::PI * 100.;
+		let c = Circle::new(Point{x: 50., y: 50.}, 10.);
+		assert!((disk_rect_area(&c, 0., 100., 0., 100.) - full).abs() < 1e-3);
+		// Halves and quarters
+		assert!((disk_rect_area(&c, 0., 50., 0., 100.) - full / 2.).abs() < 1e-3);
+		assert!((disk_rect_area(&c, 50., 100., 50., 100.) - full / 4.).abs() < 1e-3);
+		assert_eq!(disk_rect_area(&c, 70., 100., 0., 100.), 0.);
+
+		// A segment cut off at distance 5 from the center
+		let segment = 100. * (0.5f32).acos() - 5. * (75f32).sqrt();
+		assert!((disk_rect_area(&c, 55., 100., 0., 100.) - segment).abs() < 1e-3);
+		assert!((disk_rect_area(&c, 0., 100., 0., 45.) - segment).abs() < 1e-3);
+	}
+}
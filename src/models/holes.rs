@@ -0,0 +1,212 @@
+use std::f32;
+use std::collections::VecDeque;
+
+use models::raster::*;
+use super::*;
+
+/// Connected region of the field that no sensor covers
+#[derive(Debug, Clone)]
+pub struct Hole {
+	pub area: f32,
+	/// Corners of the bounding box, clipped to the field
+	pub min: Point,
+	pub max: Point,
+	pub centroid: Point,
+	/// Radius of the largest circle inside the field that touches no covered cell
+	pub radius: f32
+}
+
+/// Finds the uncovered regions of a layout on the default raster, largest first
+pub fn coverage_holes(conf: &Configuration, state: &Vec<Point>) -> Vec<Hole> {
+	let g = CoverageGrid::from_state(conf, state, DEFAULT_RESOLUTION);
+	find_holes(conf, &g)
+}
+
+/// Groups the uncovered cells of `g` into 4-connected regions, largest first
+pub fn find_holes(conf: &Configuration, g: &CoverageGrid) -> Vec<Hole> {
+	let clearance = empty_radius(conf, g);
+	let mut label: Vec<Option<usize>> = vec![None; g.cols * g.rows];
+	let mut holes: Vec<Hole> = Vec::new();
+
+	for start in 0..label.len() {
+		if label[start].is_some() || g.is_covered(start) {
+			continue;
+		}
+
+		let mut hole = Hole {
+			area: 0.,
+			min: Point::wrap(f32::MAX), max: Point::wrap(f32::MIN),
+			centroid: Point::wrap(0.),
+			radius: 0.
+		};
+		let mut queue = VecDeque::new();
+		label[start] = Some(holes.len());
+		queue.push_back(start);
+
+		while let Some(i) = queue.pop_front() {
+			let (col, row) = (i % g.cols, i / g.cols);
+			let center = g.cell_center(col, row);
+			let area = cell_area(conf, g, col, row);
+			hole.area += area;
+			hole.centroid += center * Point::wrap(area);
+			hole.min = Point{x: f32::min(hole.min.x, col as f32 * g.cell), y: f32::min(hole.min.y, row as f32 * g.cell)};
+			hole.max = Point{x: f32::max(hole.max.x, (col + 1) as f32 * g.cell), y: f32::max(hole.max.y, (row + 1) as f32 * g.cell)};
+			hole.radius = f32::max(hole.radius, clearance[i]);
+
+			let mut neighbours = Vec::new();
+			if col > 0 { neighbours.push(i - 1); }
+			if col + 1 < g.cols { neighbours.push(i + 1); }
+			if row > 0 { neighbours.push(i - g.cols); }
+			if row + 1 < g.rows { neighbours.push(i + g.cols); }
+			for j in neighbours {
+				if label[j].is_none() && !g.is_covered(j) {
+					label[j] = Some(holes.len());
+					queue.push_back(j);
+				}
+			}
+		}
+
+		hole.centroid *= Point::wrap(1. / hole.area);
+		hole.max = Point{x: f32::min(hole.max.x, conf.w), y: f32::min(hole.max.y, conf.h)};
+		holes.push(hole);
+	}
+
+	holes.sort_by(|a, b| b.area.partial_cmp(&a.area).unwrap());
+	holes
+}
+
+/// Area of a raster cell that lies inside the field
+fn cell_area(conf: &Configuration, g: &CoverageGrid, col: usize, row: usize) -> f32 {
+	let w = f32::min(g.cell, conf.w - col as f32 * g.cell);
+	let h = f32::min(g.cell, conf.h - row as f32 * g.cell);
+	w * h
+}
+
+/// Radius of the largest empty circle centred at each cell: the distance to the
+/// nearest covered cell, less half a cell, but no further than the field border
+fn empty_radius(conf: &Configuration, g: &CoverageGrid) -> Vec<f32> {
+	let sources: Vec<bool> = (0..g.cols * g.rows).map(|i| g.is_covered(i)).collect();
+	let dist = distance_transform(&sources, g.cols, g.rows);
+
+	(0..dist.len()).map(|i| {
+		let c = g.cell_center(i % g.cols, i / g.cols);
+		let border = f32::min(f32::min(c.x, conf.w - c.x), f32::min(c.y, conf.h - c.y));
+		let covered = (dist[i] as f32).sqrt() * g.cell - 0.5 * g.cell;
+		f32::max(0., f32::min(border, covered))
+	}).collect()
+}
+
+/// Exact squared Euclidean distance, in cells, from each cell to the nearest source cell
+/// (Felzenszwalb and Huttenlocher). Cells are infinitely far when there are no sources.
+fn distance_transform(sources: &[bool], cols: usize, rows: usize) -> Vec<f64> {
+	let mut d: Vec<f64> = sources.iter().map(|s| if *s { 0. } else { f64::INFINITY }).collect();
+
+	let mut line = Vec::new();
+	for col in 0..cols {
+		line = (0..rows).map(|row| d[row * cols + col]).collect();
+		let out = distance_1d(&line);
+		for row in 0..rows {
+			d[row * cols + col] = out[row];
+		}
+	}
+	for row in 0..rows {
+		line.clear();
+		line.extend_from_slice(&d[row * cols..(row + 1) * cols]);
+		let out = distance_1d(&line);
+		d[row * cols..(row + 1) * cols].copy_from_slice(&out);
+	}
+
+	d
+}
+
+/// Lower envelope of the parabolas (x - q)^2 + f(q)
+fn distance_1d(f: &[f64]) -> Vec<f64> {
+	let n = f.len();
+	let finite: Vec<usize> = (0..n).filter(|q| f[*q].is_finite()).collect();
+	if finite.is_empty() {
+		return f.to_vec();
+	}
+
+	let mut v: Vec<usize> = Vec::with_capacity(n);
+	let mut z: Vec<f64> = Vec::with_capacity(n + 1);
+	let intersect = |p: usize, q: usize| {
+		((f[q] + (q * q) as f64) - (f[p] + (p * p) as f64)) / (2. * (q as f64 - p as f64))
+	};
+	for q in finite {
+		while let Some(&p) = v.last() {
+			if v.len() > 1 && intersect(p, q) <= z[z.len() - 1] {
+				v.pop();
+				z.pop();
+			} else {
+				break;
+			}
+		}
+		if let Some(&p) = v.last() {
+			z.push(intersect(p, q));
+		}
+		v.push(q);
+	}
+
+	let mut d = vec![0.; n];
+	let mut k = 0;
+	for (x, dx) in d.iter_mut().enumerate() {
+		while k < z.len() && z[k] < x as f64 {
+			k += 1;
+		}
+		let q = v[k];
+		*dx = (x as f64 - q as f64).powi(2) + f[q];
+	}
+	d
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_holes() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 50.;
+		conf.n = 1;
+		conf.counts = vec![1];
+		conf.radius = vec![30.];
+
+		// A disk in the middle splits the field into two gaps on the left and right
+		let state = vec![Point{x: 50., y: 25.}];
+		let holes = coverage_holes(&conf, &state);
+		for h in &holes {
+			println!("{:?} {:?} {:?} {} {}", h.min, h.max, h.centroid, h.area, h.radius);
+		}
+		assert_eq!(holes.len(), 2);
+
+		// The disk pokes out above and below the field by a circular segment each
+		let segment = 900. * (25f32 / 30.).acos() - 25. * (900f32 - 625.).sqrt();
+		let free = 5000. - (f32::consts::PI * 900. - 2. * segment);
+		assert!((holes[0].area + holes[1].area - free).abs() / free < 0.02);
+		assert!((holes[0].area - holes[1].area).abs() / free < 0.02);
+
+		let left = if holes[0].centroid.x < 50. { &holes[0] } else { &holes[1] };
+		assert_eq!(left.min.x, 0.);
+		// The disk meets the field border at x = 50 - sqrt(30^2 - 25^2)
+		assert!((left.max.x - (50. - 275f32.sqrt())).abs() < 1.);
+		assert!(left.centroid.x < 20.);
+		assert!((left.centroid.y - 25.).abs() < 0.5);
+		// The largest empty circle sits in a corner, touching both borders and the disk
+		assert!((left.radius - 11.).abs() < 1.);
+	}
+
+	#[test]
+	fn test_distance_transform() {
+		let mut sources = vec![false; 25];
+		sources[0] = true;
+		sources[24] = true;
+		let d = distance_transform(&sources, 5, 5);
+		assert_eq!(d[0], 0.);
+		assert_eq!(d[2], 4.);
+		assert_eq!(d[12], 8.);
+		assert_eq!(d[4], 16.);
+
+		let none = distance_transform(&[false; 4], 2, 2);
+		assert!(none.iter().all(|x| x.is_infinite()));
+	}
+}
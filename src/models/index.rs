@@ -0,0 +1,152 @@
+use std::f32;
+
+use super::*;
+
+/// Uniform grid over the sensors' bounding boxes. Each bucket lists
+/// the sensors whose reach overlaps it, for fast point queries.
+#[derive(Debug, Clone)]
+pub struct GridIndex {
+	pub origin: Point,
+	pub cell: f32,
+	pub cols: usize, pub rows: usize,
+	buckets: Vec<Vec<usize>>
+}
+
+impl GridIndex {
+	pub fn new(conf: &Configuration, circles: &Vec<Circle>) -> GridIndex {
+		let (mut x0, mut y0, mut x1, mut y1) = (0f32, 0f32, conf.w, conf.h);
+		let mut max_reach = 0f32;
+		for c in circles {
+			let reach = c.sensing.reach(c.radius);
+			x0 = f32::min(x0, c.center.x - reach);
+			y0 = f32::min(y0, c.center.y - reach);
+			x1 = f32::max(x1, c.center.x + reach);
+			y1 = f32::max(y1, c.center.y + reach);
+			max_reach = f32::max(max_reach, reach);
+		}
+
+		// Cells about as large as a sensor keep buckets short
+		// without registering a sensor in many cells
+		let cell = f32::max(max_reach, f32::max(x1 - x0, y1 - y0) / 1000.);
+		let cell = if cell > 0. { cell } else { 1. };
+		let cols = ((x1 - x0) / cell).ceil() as usize + 1;
+		let rows = ((y1 - y0) / cell).ceil() as usize + 1;
+
+		let mut index = GridIndex {
+			origin: Point{x: x0, y: y0},
+			cell, cols, rows,
+			buckets: vec![Vec::new(); cols * rows]
+		};
+		for (i, c) in circles.iter().enumerate() {
+			index.insert(i, c);
+		}
+
+		index
+	}
+
+	/// Registers sensor `i` in every cell its reach overlaps
+	pub fn insert(&mut self, i: usize, c: &Circle) {
+		let (c0, r0, c1, r1) = self.cells_of(c);
+		for row in r0..r1+1 {
+			for col in c0..c1+1 {
+				self.buckets[row * self.cols + col].push(i);
+			}
+		}
+	}
+
+	/// Unregisters sensor `i`, which was inserted as `c`
+	pub fn remove(&mut self, i: usize, c: &Circle) {
+		let (c0, r0, c1, r1) = self.cells_of(c);
+		for row in r0..r1+1 {
+			for col in c0..c1+1 {
+				self.buckets[row * self.cols + col].retain(|j| *j != i);
+			}
+		}
+	}
+
+	/// Range of cells, first and last column and row, overlapped by the reach of `c`
+	fn cells_of(&self, c: &Circle) -> (usize, usize, usize, usize) {
+		let reach = c.sensing.reach(c.radius);
+		let (c0, r0) = self.cell_of(&Point{x: c.center.x - reach, y: c.center.y - reach});
+		let (c1, r1) = self.cell_of(&Point{x: c.center.x + reach, y: c.center.y + reach});
+		(c0, r0, c1, r1)
+	}
+
+	fn cell_of(&self, p: &Point) -> (usize, usize) {
+		let col = f32::max(0., ((p.x - self.origin.x) / self.cell).floor()) as usize;
+		let row = f32::max(0., ((p.y - self.origin.y) / self.cell).floor()) as usize;
+		(usize::min(col, self.cols - 1), usize::min(row, self.rows - 1))
+	}
+
+	/// Sensors that may detect `p`
+	pub fn candidates(&self, p: &Point) -> &[usize] {
+		let (col, row) = self.cell_of(p);
+		&self.buckets[row * self.cols + col]
+	}
+
+	/// Sensors that detect `p` with non-zero probability
+	pub fn covering(&self, circles: &[Circle], p: &Point) -> Vec<usize> {
+		self.candidates(p).iter()
+			.cloned()
+			.filter(|i| circles[*i].detection(p) > 0.)
+			.collect()
+	}
+
+	/// Sensors whose reach may intersect that of sensor `i`, excluding `i`
+	pub fn neighbours(&self, circles: &[Circle], i: usize) -> Vec<usize> {
+		let (c0, r0, c1, r1) = self.cells_of(&circles[i]);
+
+		let mut v: Vec<usize> = Vec::new();
+		for row in r0..r1+1 {
+			for col in c0..c1+1 {
+				v.extend(self.buckets[row * self.cols + col].iter().filter(|j| **j != i));
+			}
+		}
+		v.sort();
+		v.dedup();
+		v
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ga::init::{random_init, random_points};
+
+	fn instance(n: i32, radius: f32) -> (Configuration, Vec<Circle>) {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = n;
+		conf.counts = vec![n];
+		conf.radius = vec![radius];
+		let state = random_init(&conf, 1).pop().unwrap();
+		let circles = Circle::from_state(&conf, &state);
+		(conf, circles)
+	}
+
+	#[test]
+	fn test_queries() {
+		let (conf, circles) = instance(50, 8.);
+		let index = GridIndex::new(&conf, &circles);
+
+		for p in random_points(&conf, 1000) {
+			let mut expected: Vec<usize> = (0..circles.len())
+				.filter(|i| circles[*i].contains(&p))
+				.collect();
+			let mut found = index.covering(&circles, &p);
+			expected.sort();
+			found.sort();
+			assert_eq!(found, expected);
+		}
+
+		for i in 0..circles.len() {
+			let nb = index.neighbours(&circles, i);
+			for j in 0..circles.len() {
+				let d = circles[i].center.distance(&circles[j].center);
+				if j != i && d < 16. {
+					assert!(nb.contains(&j));
+				}
+			}
+		}
+	}
+}
@@ -1,14 +1,41 @@
 pub mod point;
 pub mod adaptive;
+pub mod sensing;
+pub mod raster;
+pub mod network;
+pub mod geometry;
+pub mod index;
+pub mod sampling;
+pub mod holes;
+pub mod contribution;
+pub mod failure;
+pub mod evaluator;
+
+use std::f32::consts::PI;
 
 use self::point::*;
+use self::sensing::SensingModel;
+
+/// Field of view of an omnidirectional sensor
+pub const FULL_CIRCLE: f32 = 2. * PI;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Configuration {
 	pub w: f32, pub h: f32,
 	pub n: i32,
 	pub counts: Vec<i32>,
-	pub radius: Vec<f32>
+	pub radius: Vec<f32>,
+	pub sensing: Vec<SensingModel>,
+	pub fov: Vec<f32>,
+	pub comm: Vec<f32>,
+	pub sinks: Vec<Point>,
+	pub free_sinks: i32,
+	pub threshold: f32,
+	pub cost: Vec<f32>,
+	/// Pre-installed sensors that count toward coverage but are never moved
+	pub fixed: Vec<Circle>,
+	/// Counts are upper bounds and each sensor carries an activation gene
+	pub variable_counts: bool
 }
 
 impl Configuration {
@@ -17,36 +44,305 @@ impl Configuration {
 			w: 0., h: 0., n: 0,
 			counts: Vec::new(),
 			radius: Vec::new(),
+			sensing: Vec::new(),
+			fov: Vec::new(),
+			comm: Vec::new(),
+			sinks: Vec::new(),
+			free_sinks: 0,
+			threshold: 0.9,
+			cost: Vec::new(),
+			fixed: Vec::new(),
+			variable_counts: false
+		}
+	}
+
+	/// Sensing model of type `t`. Types without an explicit model use the binary disk.
+	pub fn sensing_model(&self, t: usize) -> SensingModel {
+		match self.sensing.get(t) {
+			Some(m) => *m,
+			None => SensingModel::Binary
+		}
+	}
+
+	/// Field of view of type `t` in radians. Types without an explicit angle cover a full disk.
+	pub fn fov_of(&self, t: usize) -> f32 {
+		match self.fov.get(t) {
+			Some(a) => *a,
+			None => FULL_CIRCLE
+		}
+	}
+
+	/// Communication radius of type `t`. Defaults to twice the sensing radius.
+	pub fn comm_of(&self, t: usize) -> f32 {
+		match self.comm.get(t) {
+			Some(r) => *r,
+			None => 2. * self.radius[t]
+		}
+	}
+
+	/// Price of one sensor of type `t`. Defaults to 1.
+	pub fn cost_of(&self, t: usize) -> f32 {
+		match self.cost.get(t) {
+			Some(c) => *c,
+			None => 1.
 		}
 	}
+
+	pub fn is_sector(&self, t: usize) -> bool {
+		self.fov_of(t) < FULL_CIRCLE
+	}
+
+	/// Number of sensors that need an orientation gene
+	pub fn sector_count(&self) -> i32 {
+		let mut s = 0;
+		for t in 0..self.counts.len() {
+			if self.is_sector(t) {
+				s += self.counts[t];
+			}
+		}
+		s
+	}
+
+	/// Length of a state: one position per sensor, followed by one
+	/// heading gene per sector sensor in the same order, one activation gene
+	/// per sensor with variable counts, and the positions of the free sinks
+	pub fn genome_len(&self) -> usize {
+		self.sink_genes() + self.free_sinks as usize
+	}
+
+	/// Index of the first activation gene
+	pub fn flag_genes(&self) -> usize {
+		(self.n + self.sector_count()) as usize
+	}
+
+	/// Index of the first free sink gene
+	pub fn sink_genes(&self) -> usize {
+		if self.variable_counts {
+			self.flag_genes() + self.n as usize
+		} else {
+			self.flag_genes()
+		}
+	}
+
+	/// Whether sensor `i` is deployed. Sensors are active when their activation gene is at least 0.5.
+	pub fn is_active(&self, state: &[Point], i: usize) -> bool {
+		!self.variable_counts || state[self.flag_genes() + i].x >= 0.5
+	}
+
+	/// Configuration and state holding only the active sensors
+	pub fn active_layout(&self, state: &[Point]) -> (Configuration, Vec<Point>) {
+		let mut conf = self.clone();
+		conf.variable_counts = false;
+
+		let mut positions: Vec<Point> = Vec::new();
+		let mut headings: Vec<Point> = Vec::new();
+		let mut _i = 0;
+		let mut _s = self.n as usize;
+		for t in 0..self.counts.len() {
+			let mut count = 0;
+			for j in _i.._i + self.counts[t] as usize {
+				let active = self.is_active(state, j);
+				if active {
+					positions.push(state[j]);
+					count += 1;
+				}
+				if self.is_sector(t) {
+					if active {
+						headings.push(state[_s]);
+					}
+					_s += 1;
+				}
+			}
+			conf.counts[t] = count;
+			_i += self.counts[t] as usize;
+		}
+		conf.n = positions.len() as i32;
+
+		positions.extend(headings);
+		positions.extend_from_slice(&state[self.sink_genes()..]);
+		(conf, positions)
+	}
+
+	/// Total price of the sensors in the configuration
+	pub fn total_cost(&self) -> f32 {
+		(0..self.counts.len())
+			.map(|t| self.counts[t] as f32 * self.cost_of(t))
+			.sum()
+	}
+
+	/// Fixed sinks followed by the free sinks placed by the state
+	pub fn sink_positions(&self, state: &[Point]) -> Vec<Point> {
+		let mut sinks = self.sinks.clone();
+		sinks.extend_from_slice(&state[self.sink_genes()..]);
+		sinks
+	}
 }
 
+/// Wraps an angle into [-PI, PI)
+pub fn wrap_angle(a: f32) -> f32 {
+	let mut a = (a + PI) % FULL_CIRCLE;
+	if a < 0. {
+		a += FULL_CIRCLE;
+	}
+	a - PI
+}
+
+/// A sensor's coverage region. Sectors are described by their
+/// field of view and the heading of its bisector, both in radians.
 #[derive(Debug, Clone, Copy)]
 pub struct Circle {
 	pub center: Point,
-	pub radius: f32
+	pub radius: f32,
+	pub sensing: SensingModel,
+	pub fov: f32,
+	pub heading: f32
 }
 
 impl Circle {
+	/// A binary omnidirectional sensor
+	pub fn new(center: Point, radius: f32) -> Circle {
+		Circle {
+			center, radius,
+			sensing: SensingModel::Binary,
+			fov: FULL_CIRCLE, heading: 0.
+		}
+	}
+
+	pub fn is_sector(&self) -> bool {
+		self.fov < FULL_CIRCLE
+	}
+
+	/// Checks whether `p` lies in the angular range of the sensor
+	pub fn in_view(&self, p: &Point) -> bool {
+		if !self.is_sector() || p.equals(&self.center) {
+			return true;
+		}
+		let a = (*p - self.center).angle();
+		wrap_angle(a - self.heading).abs() <= self.fov / 2.
+	}
+
+	#[allow(dead_code)]
 	pub fn contains(&self, p: &Point) -> bool {
 		let d = self.center.distance(p);
-		d <= self.radius
+		d <= self.radius && self.in_view(p)
+	}
+
+	/// Probability that this sensor detects `p`
+	pub fn detection(&self, p: &Point) -> f32 {
+		if !self.in_view(p) {
+			return 0.;
+		}
+		let d = self.center.distance(p);
+		self.sensing.probability(self.radius, d)
 	}
 
+	/// Sensors of a state in genome order, followed by the fixed sensors
 	pub fn from_state(conf: &Configuration, state: &Vec<Point>) -> Vec<Circle> {
+		debug_assert_eq!(state.len(), conf.genome_len());
 		let mut v: Vec<Circle> = Vec::new();
 
 		let mut _i = 0;
+		let mut _s = conf.n;
 		for i in 0..conf.counts.len() {
 			let count = conf.counts[i];
+			let sector = conf.is_sector(i);
 			for j in _i..(_i + count) {
+				let mut heading = 0.;
+				if sector {
+					heading = state[_s as usize].angle();
+					_s += 1;
+				}
 				v.push(Circle{
 					center: state[j as usize].clone(), 
-					radius: conf.radius[i]});
+					radius: conf.radius[i],
+					sensing: conf.sensing_model(i),
+					fov: conf.fov_of(i),
+					heading});
 			}
 			_i += count;
 		}
+		v.extend_from_slice(&conf.fixed);
 
 		v
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_wrap_angle() {
+		assert!((wrap_angle(3. * PI / 2.) + PI / 2.).abs() < 1e-5);
+		assert!((wrap_angle(-3. * PI / 2.) - PI / 2.).abs() < 1e-5);
+		assert!((wrap_angle(0.25) - 0.25).abs() < 1e-6);
+	}
+
+	#[test]
+	fn test_active_layout() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 3;
+		conf.counts = vec![2, 1];
+		conf.radius = vec![10., 20.];
+		conf.fov = vec![PI, FULL_CIRCLE];
+		conf.cost = vec![5., 12.];
+		conf.free_sinks = 1;
+		conf.variable_counts = true;
+		assert_eq!(conf.genome_len(), 9);
+
+		let state = vec![
+			Point::wrap(10.), Point::wrap(20.), Point::wrap(30.),
+			Point{x: 1., y: 0.}, Point{x: 0., y: 1.},
+			Point{x: 0.2, y: 0.}, Point{x: 0.9, y: 0.}, Point{x: 0.5, y: 0.},
+			Point::wrap(50.)
+		];
+		let (active, s) = conf.active_layout(&state);
+		assert_eq!(active.counts, vec![1, 1]);
+		assert_eq!(active.n, 2);
+		assert_eq!(active.genome_len(), s.len());
+		assert!(Point::all_equal(&s, &vec![Point::wrap(20.), Point::wrap(30.), Point{x: 0., y: 1.}, Point::wrap(50.)]));
+		assert_eq!(active.total_cost(), 17.);
+	}
+
+	#[test]
+	fn test_fixed() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 1;
+		conf.counts = vec![1];
+		conf.radius = vec![10.];
+		conf.fixed = vec![Circle::new(Point::wrap(80.), 15.)];
+		assert_eq!(conf.genome_len(), 1);
+
+		let circles = Circle::from_state(&conf, &vec![Point::wrap(20.)]);
+		assert_eq!(circles.len(), 2);
+		assert_eq!(circles[1].radius, 15.);
+		assert!(circles[1].contains(&Point::wrap(75.)));
+	}
+
+	#[test]
+	fn test_sector() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 2;
+		conf.counts = vec![1, 1];
+		conf.radius = vec![10., 10.];
+		conf.fov = vec![FULL_CIRCLE, PI / 2.];
+		assert_eq!(conf.genome_len(), 3);
+
+		// The sector faces west, across the wrap at PI
+		let state = vec![Point{x: 20., y: 20.}, Point{x: 50., y: 50.}, Point{x: -1., y: 0.}];
+		let circles = Circle::from_state(&conf, &state);
+		assert!(!circles[0].is_sector());
+		assert!(circles[1].is_sector());
+
+		assert!(circles[1].contains(&Point{x: 42., y: 50.}));
+		assert!(circles[1].contains(&Point{x: 45., y: 51.}));
+		assert!(circles[1].contains(&Point{x: 45., y: 49.}));
+		assert!(!circles[1].contains(&Point{x: 55., y: 50.}));
+		assert!(!circles[1].contains(&Point{x: 50., y: 45.}));
+		assert_eq!(circles[1].detection(&Point{x: 55., y: 50.}), 0.);
+		assert!(circles[0].contains(&Point{x: 25., y: 20.}));
+	}
+}
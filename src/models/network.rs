@@ -0,0 +1,353 @@
+use std::collections::VecDeque;
+use std::f32;
+
+use super::*;
+
+/// Unit-disk communication graph over the sensors, followed by the sinks.
+/// Two sensors are linked when both can reach each other, a sensor and a sink
+/// when the sink is within the sensor's communication radius.
+#[derive(Debug, Clone)]
+pub struct Network {
+	pub positions: Vec<Point>,
+	pub comm: Vec<f32>,
+	pub sensors: usize,
+	pub adj: Vec<Vec<usize>>
+}
+
+impl Network {
+	pub fn from_state(conf: &Configuration, state: &[Point]) -> Network {
+		Network::with_sinks(conf, state, &conf.sink_positions(state))
+	}
+
+	pub fn with_sinks(conf: &Configuration, state: &[Point], sinks: &Vec<Point>) -> Network {
+		let mut positions: Vec<Point> = Vec::new();
+		let mut comm: Vec<f32> = Vec::new();
+
+		let mut _i = 0;
+		for t in 0..conf.counts.len() {
+			let count = conf.counts[t] as usize;
+			for p in &state[_i..(_i + count)] {
+				positions.push(*p);
+				comm.push(conf.comm_of(t));
+			}
+			_i += count;
+		}
+		let sensors = positions.len();
+		for s in sinks {
+			positions.push(*s);
+			comm.push(0.);
+		}
+
+		let mut net = Network {
+			positions, comm,
+			sensors,
+			adj: Vec::new()
+		};
+		net.build_links();
+		net
+	}
+
+	/// Largest distance over which nodes `i` and `j` can communicate
+	pub fn link_range(&self, i: usize, j: usize) -> f32 {
+		if i >= self.sensors {
+			self.comm[j]
+		} else if j >= self.sensors {
+			self.comm[i]
+		} else {
+			f32::min(self.comm[i], self.comm[j])
+		}
+	}
+
+	pub fn build_links(&mut self) {
+		let n = self.positions.len();
+		self.adj = vec![Vec::new(); n];
+		for i in 0..n {
+			for j in i+1..n {
+				if i >= self.sensors && j >= self.sensors {
+					continue;
+				}
+				if self.positions[i].distance(&self.positions[j]) <= self.link_range(i, j) {
+					self.adj[i].push(j);
+					self.adj[j].push(i);
+				}
+			}
+		}
+	}
+
+	/// Hop distance from the given sources to every node, None when unreachable
+	pub fn hops_from(&self, sources: &[usize]) -> Vec<Option<usize>> {
+		let mut hops = vec![None; self.positions.len()];
+		let mut q: VecDeque<usize> = VecDeque::new();
+		for s in sources {
+			hops[*s] = Some(0);
+			q.push_back(*s);
+		}
+
+		while let Some(u) = q.pop_front() {
+			let h = hops[u].unwrap();
+			for v in &self.adj[u] {
+				if hops[*v].is_none() {
+					hops[*v] = Some(h + 1);
+					q.push_back(*v);
+				}
+			}
+		}
+
+		hops
+	}
+
+	/// Component label of every node
+	pub fn components(&self) -> Vec<usize> {
+		let n = self.positions.len();
+		let mut label = vec![usize::MAX; n];
+		let mut next = 0;
+		for i in 0..n {
+			if label[i] != usize::MAX {
+				continue;
+			}
+			let hops = self.hops_from(&[i]);
+			for j in 0..n {
+				if hops[j].is_some() {
+					label[j] = next;
+				}
+			}
+			next += 1;
+		}
+		label
+	}
+
+	/// Number of connected components among the sensors
+	pub fn component_count(&self) -> usize {
+		let label = self.components();
+		let mut seen: Vec<usize> = label[..self.sensors].to_vec();
+		seen.sort();
+		seen.dedup();
+		seen.len()
+	}
+
+	/// Largest hop distance between two connected sensors
+	pub fn diameter(&self) -> usize {
+		let mut d = 0;
+		for i in 0..self.sensors {
+			let hops = self.hops_from(&[i]);
+			for h in hops[..self.sensors].iter().flatten() {
+				d = usize::max(d, *h);
+			}
+		}
+		d
+	}
+
+	/// Sensors that belong to the main network: those with a path to a sink
+	/// when `require_sink` is set, the largest component otherwise
+	pub fn connected(&self, require_sink: bool) -> Vec<bool> {
+		if require_sink {
+			let sinks: Vec<usize> = (self.sensors..self.positions.len()).collect();
+			let hops = self.hops_from(&sinks);
+			return hops[..self.sensors].iter().map(|h| h.is_some()).collect();
+		}
+
+		let label = self.components();
+		let mut sizes = vec![0; label.len()];
+		for l in &label[..self.sensors] {
+			sizes[*l] += 1;
+		}
+		let mut main = 0;
+		for l in 0..sizes.len() {
+			if sizes[l] > sizes[main] {
+				main = l;
+			}
+		}
+		label[..self.sensors].iter().map(|l| *l == main).collect()
+	}
+
+	/// Fraction of sensors in the main network
+	pub fn connected_fraction(&self, require_sink: bool) -> f32 {
+		if self.sensors == 0 {
+			return 1.;
+		}
+		let c = self.connected(require_sink).iter().filter(|c| **c).count();
+		c as f32 / self.sensors as f32
+	}
+}
+
+/// How routes towards the sinks are chosen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Routing {
+	MinHop,
+	ShortestPath
+}
+
+/// Routes from each sensor to its closest sink. Node indices follow `Network`:
+/// sensors first, then sinks.
+#[derive(Debug, Clone)]
+pub struct RoutingTree {
+	/// Next node towards the sink
+	pub parent: Vec<Option<usize>>,
+	/// Sink the route ends at
+	pub sink: Vec<Option<usize>>,
+	pub hops: Vec<Option<usize>>,
+	/// Path length of the route
+	pub length: Vec<f32>,
+	/// Number of other sensors whose packets a node forwards
+	pub load: Vec<usize>
+}
+
+impl RoutingTree {
+	pub fn new(net: &Network, routing: Routing) -> RoutingTree {
+		let n = net.positions.len();
+		let mut tree = RoutingTree {
+			parent: vec![None; n],
+			sink: vec![None; n],
+			hops: vec![None; n],
+			length: vec![f32::INFINITY; n],
+			load: vec![0; n]
+		};
+
+		// Dijkstra from all sinks. With unit edge weights this is a BFS for minimum hops.
+		let mut done = vec![false; n];
+		for s in net.sensors..n {
+			tree.sink[s] = Some(s - net.sensors);
+			tree.hops[s] = Some(0);
+			tree.length[s] = 0.;
+		}
+		let cost = |u: usize, v: usize| match routing {
+			Routing::MinHop => 1.,
+			Routing::ShortestPath => net.positions[u].distance(&net.positions[v])
+		};
+		let key = |t: &RoutingTree, u: usize| match routing {
+			Routing::MinHop => t.hops[u].map(|h| h as f32).unwrap_or(f32::INFINITY),
+			Routing::ShortestPath => t.length[u]
+		};
+
+		loop {
+			let mut u: Option<usize> = None;
+			for (i, d) in done.iter().enumerate() {
+				if *d || tree.hops[i].is_none() {
+					continue;
+				}
+				if u.is_none_or(|j| key(&tree, i) < key(&tree, j)) {
+					u = Some(i);
+				}
+			}
+			let u = match u {
+				Some(u) => u,
+				None => break
+			};
+			done[u] = true;
+
+			for v in &net.adj[u] {
+				let v = *v;
+				if done[v] || v >= net.sensors {
+					continue;
+				}
+				let hops = tree.hops[u].unwrap() + 1;
+				let length = tree.length[u] + net.positions[u].distance(&net.positions[v]);
+				let better = match tree.hops[v] {
+					None => true,
+					Some(_) => key(&tree, u) + cost(u, v) < key(&tree, v)
+				};
+				if better {
+					tree.parent[v] = Some(u);
+					tree.sink[v] = tree.sink[u];
+					tree.hops[v] = Some(hops);
+					tree.length[v] = length;
+				}
+			}
+		}
+
+		// Every sensor loads the nodes on its route
+		for i in 0..net.sensors {
+			let mut p = tree.parent[i];
+			while let Some(j) = p {
+				if j >= net.sensors {
+					break;
+				}
+				tree.load[j] += 1;
+				p = tree.parent[j];
+			}
+		}
+
+		tree
+	}
+
+	pub fn max_load(&self) -> usize {
+		self.load.iter().cloned().max().unwrap_or(0)
+	}
+
+	/// Rounds until the first sensor runs out of energy, when every sensor sends one
+	/// packet per round and a battery lasts for `battery` transmissions
+	pub fn lifetime(&self, battery: f32) -> f32 {
+		battery / (1 + self.max_load()) as f32
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn conf() -> Configuration {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 4;
+		conf.counts = vec![4];
+		conf.radius = vec![5.];
+		conf.comm = vec![10.];
+		conf
+	}
+
+	#[test]
+	fn test_components() {
+		let conf = conf();
+		let state = vec![
+			Point{x: 10., y: 10.}, Point{x: 18., y: 10.}, Point{x: 26., y: 10.},
+			Point{x: 80., y: 80.}
+		];
+		let net = Network::from_state(&conf, &state);
+		assert_eq!(net.component_count(), 2);
+		assert_eq!(net.diameter(), 2);
+		assert_eq!(net.connected(false), vec![true, true, true, false]);
+		assert_eq!(net.connected_fraction(false), 0.75);
+	}
+
+	#[test]
+	fn test_sink() {
+		let mut conf = conf();
+		conf.sinks = vec![Point{x: 85., y: 80.}];
+		let state = vec![
+			Point{x: 10., y: 10.}, Point{x: 18., y: 10.}, Point{x: 26., y: 10.},
+			Point{x: 80., y: 80.}
+		];
+		let net = Network::from_state(&conf, &state);
+		assert_eq!(net.connected(true), vec![false, false, false, true]);
+		assert_eq!(net.hops_from(&[4])[3], Some(1));
+	}
+
+	#[test]
+	fn test_routing_tree() {
+		let mut conf = conf();
+		conf.n = 5;
+		conf.counts = vec![5];
+		conf.sinks = vec![Point{x: 0., y: 10.}];
+		// 3 reaches the sink in 3 hops through either 1 or 2, the shorter route goes through 2
+		let state = vec![
+			Point{x: 8., y: 10.}, Point{x: 9., y: 17.}, Point{x: 14., y: 10.},
+			Point{x: 18., y: 15.}, Point{x: 80., y: 80.}
+		];
+		let net = Network::from_state(&conf, &state);
+
+		let tree = RoutingTree::new(&net, Routing::MinHop);
+		println!("{:?}", tree);
+		assert_eq!(tree.hops[0], Some(1));
+		assert_eq!(tree.parent[0], Some(5));
+		assert_eq!(tree.hops[3], Some(3));
+		assert_eq!(tree.hops[4], None);
+		assert_eq!(tree.sink[3], Some(0));
+		assert_eq!(tree.load[0], 3);
+		assert_eq!(tree.max_load(), 3);
+		assert_eq!(tree.lifetime(400.), 100.);
+
+		let tree = RoutingTree::new(&net, Routing::ShortestPath);
+		assert_eq!(tree.parent[3], Some(2));
+		assert!((tree.length[3] - (8. + 6. + 41f32.sqrt())).abs() < 1e-4);
+	}
+}
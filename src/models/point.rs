@@ -16,6 +16,18 @@ impl Point {
 		self.x == other.x && self.y == other.y
 	}
 
+	/// Angle of the vector from the origin to this point
+	pub fn angle(&self) -> f32 {
+		self.y.atan2(self.x)
+	}
+
+	/// Unit vector pointing at angle `a`
+	pub fn from_angle(a: f32) -> Point {
+		Point {
+			x: a.cos(), y: a.sin()
+		}
+	}
+
 	pub fn wrap(value: f32) -> Point {
 		Point {
 			x: value, y: value
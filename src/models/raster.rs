@@ -0,0 +1,167 @@
+use std::f32;
+
+use super::*;
+
+/// Number of cells along the longer side of the field
+pub const DEFAULT_RESOLUTION: usize = 200;
+
+/// Regular raster over the field, tracking how each cell centre is covered.
+/// `depth` counts the sensors that detect a cell with at least `conf.threshold` probability.
+/// `certain` counts the sensors that always detect it, `miss` is the probability that
+/// none of the others does. Keeping both apart lets sensors be removed again.
+#[derive(Debug, Clone)]
+pub struct CoverageGrid {
+	pub cols: usize, pub rows: usize,
+	pub cell: f32,
+	pub depth: Vec<u16>,
+	pub certain: Vec<u16>,
+	pub miss: Vec<f64>,
+	threshold: f32
+}
+
+impl CoverageGrid {
+	pub fn new(conf: &Configuration, resolution: usize) -> CoverageGrid {
+		let cell = f32::max(conf.w, conf.h) / resolution as f32;
+		let cols = (conf.w / cell).ceil() as usize;
+		let rows = (conf.h / cell).ceil() as usize;
+
+		CoverageGrid {
+			cols, rows, cell,
+			depth: vec![0; cols * rows],
+			certain: vec![0; cols * rows],
+			miss: vec![1.; cols * rows],
+			threshold: conf.threshold
+		}
+	}
+
+	pub fn from_state(conf: &Configuration, state: &Vec<Point>, resolution: usize) -> CoverageGrid {
+		let mut g = CoverageGrid::new(conf, resolution);
+		for c in Circle::from_state(conf, state) {
+			g.add(&c);
+		}
+		g
+	}
+
+	pub fn cell_center(&self, col: usize, row: usize) -> Point {
+		Point {
+			x: (col as f32 + 0.5) * self.cell,
+			y: (row as f32 + 0.5) * self.cell
+		}
+	}
+
+	/// Checks whether cell `i` is detected with at least the threshold probability
+	pub fn is_covered(&self, i: usize) -> bool {
+		self.certain[i] > 0 || 1. - self.miss[i] >= self.threshold as f64
+	}
+
+	/// Adds a sensor to the raster, visiting only cells within its reach.
+	/// Returns the change in the number of covered cells.
+	pub fn add(&mut self, c: &Circle) -> isize {
+		self.update(c, true)
+	}
+
+	/// Removes a sensor previously added with `add`.
+	/// Returns the change in the number of covered cells.
+	pub fn remove(&mut self, c: &Circle) -> isize {
+		self.update(c, false)
+	}
+
+	fn update(&mut self, c: &Circle, add: bool) -> isize {
+		let reach = c.sensing.reach(c.radius);
+		let (c0, c1) = self.span(c.center.x - reach, c.center.x + reach, self.cols);
+		let (r0, r1) = self.span(c.center.y - reach, c.center.y + reach, self.rows);
+		let mut delta = 0;
+
+		for row in r0..r1 {
+			for col in c0..c1 {
+				let p = c.detection(&self.cell_center(col, row));
+				if p == 0. {
+					continue;
+				}
+				let i = row * self.cols + col;
+				let before = self.is_covered(i);
+				if add {
+					if p >= 1. {
+						self.certain[i] += 1;
+					} else {
+						self.miss[i] *= 1. - p as f64;
+					}
+					if p >= self.threshold {
+						self.depth[i] += 1;
+					}
+				} else {
+					if p >= 1. {
+						self.certain[i] -= 1;
+					} else {
+						self.miss[i] = f64::min(1., self.miss[i] / (1. - p as f64));
+					}
+					if p >= self.threshold {
+						self.depth[i] -= 1;
+					}
+				}
+				match (before, self.is_covered(i)) {
+					(false, true) => delta += 1,
+					(true, false) => delta -= 1,
+					_ => ()
+				}
+			}
+		}
+
+		delta
+	}
+
+	/// Number of covered cells
+	pub fn covered_cells(&self) -> usize {
+		(0..self.miss.len()).filter(|i| self.is_covered(*i)).count()
+	}
+
+	fn span(&self, start: f32, end: f32, max: usize) -> (usize, usize) {
+		let s = f32::max(0., (start / self.cell).floor()) as usize;
+		let e = f32::max(0., (end / self.cell).ceil()) as usize;
+		(usize::min(s, max), usize::min(e, max))
+	}
+
+	/// Fraction of cells whose detection probability reaches the threshold
+	pub fn coverage_fraction(&self) -> f32 {
+		self.covered_cells() as f32 / self.miss.len() as f32
+	}
+
+	/// Fraction of cells by coverage depth. Bin i holds depth i for i < k, the last bin holds depth >= k.
+	pub fn depth_histogram(&self, k: usize) -> Vec<f32> {
+		let mut hist = vec![0.; k + 1];
+		for d in &self.depth {
+			hist[usize::min(*d as usize, k)] += 1.;
+		}
+		let total = self.depth.len() as f32;
+		for h in &mut hist {
+			*h /= total;
+		}
+		hist
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_depth_histogram() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 2;
+		conf.counts = vec![2];
+		conf.radius = vec![20.];
+		let state = vec![Point{x: 40., y: 50.}, Point{x: 60., y: 50.}];
+
+		let g = CoverageGrid::from_state(&conf, &state, 200);
+		let hist = g.depth_histogram(2);
+		println!("{:?}", hist);
+
+		// Two disks of radius 20 at distance 20: each is ~0.1257, the lens ~0.0491
+		let lens = 2. * 400. * (0.5f32).acos() - 10. * (1600f32 - 400.).sqrt();
+		assert!((hist[2] - lens / 10000.).abs() < 2e-3);
+		assert!((hist[1] - (2. * 0.04 * f32::consts::PI - 2. * lens / 10000.)).abs() < 2e-3);
+		assert!((hist[0] + hist[1] + hist[2] - 1.).abs() < 1e-4);
+		assert!((g.coverage_fraction() - hist[1] - hist[2]).abs() < 1e-4);
+	}
+}
@@ -0,0 +1,187 @@
+use rand::distributions::{Range, IndependentSample};
+use random;
+
+use ga::init::random_points;
+use super::*;
+
+/// Number of independently randomized replicates behind each estimate
+pub const REPLICATES: usize = 10;
+
+/// How sample points are spread over the field
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sampler {
+	/// Independent uniform points
+	Random,
+	/// Halton sequence in bases 2 and 3
+	Halton,
+	/// Two-dimensional Sobol sequence
+	Sobol,
+	/// One jittered point in each cell of a regular grid
+	Stratified
+}
+
+impl Sampler {
+	pub fn from_name(name: &str) -> Option<Sampler> {
+		match name {
+			"random" => Some(Sampler::Random),
+			"halton" => Some(Sampler::Halton),
+			"sobol" => Some(Sampler::Sobol),
+			"stratified" => Some(Sampler::Stratified),
+			_ => None
+		}
+	}
+
+	/// Creates about `count` points over the field. Low-discrepancy sequences
+	/// get a random shift (modulo the field) so that repeated calls are independent.
+	pub fn points(&self, conf: &Configuration, count: usize) -> Vec<Point> {
+		let mut r = random::thread_rng();
+		let unit = Range::new(0., 1.);
+		let shift = (unit.ind_sample(&mut r), unit.ind_sample(&mut r));
+		let scale = |u: f64, v: f64| Point {
+			x: (((u + shift.0) % 1.) * conf.w as f64) as f32,
+			y: (((v + shift.1) % 1.) * conf.h as f64) as f32
+		};
+
+		match *self {
+			Sampler::Random => random_points(conf, count as i32),
+			Sampler::Halton => (0..count)
+				.map(|i| scale(radical_inverse(i as u32, 2), radical_inverse(i as u32, 3)))
+				.collect(),
+			Sampler::Sobol => (0..count)
+				.map(|i| { let (u, v) = sobol(i as u32); scale(u, v) })
+				.collect(),
+			Sampler::Stratified => {
+				// Cells roughly as square as the field allows
+				let cols = usize::max(1, ((count as f32 * conf.w / conf.h).sqrt()).round() as usize);
+				let rows = usize::max(1, (count as f32 / cols as f32).round() as usize);
+				let mut points = Vec::with_capacity(cols * rows);
+				for row in 0..rows {
+					for col in 0..cols {
+						points.push(Point {
+							x: ((col as f64 + unit.ind_sample(&mut r)) / cols as f64 * conf.w as f64) as f32,
+							y: ((row as f64 + unit.ind_sample(&mut r)) / rows as f64 * conf.h as f64) as f32
+						});
+					}
+				}
+				points
+			}
+		}
+	}
+}
+
+/// Sampler and number of samples used to estimate coverage
+#[derive(Debug, Clone, Copy)]
+pub struct Sampling {
+	pub sampler: Sampler,
+	pub samples: usize
+}
+
+impl Sampling {
+	pub fn new() -> Sampling {
+		Sampling { sampler: Sampler::Halton, samples: 1000000 }
+	}
+}
+
+impl Default for Sampling {
+	fn default() -> Sampling {
+		Sampling::new()
+	}
+}
+
+/// Estimated value with its standard error
+#[derive(Debug, Clone, Copy)]
+pub struct Estimate {
+	pub value: f32,
+	pub error: f32
+}
+
+impl Estimate {
+	/// Mean and standard error of the mean of independent replicates
+	pub fn from_replicates(values: &[f64]) -> Estimate {
+		let n = values.len() as f64;
+		let mean = values.iter().sum::<f64>() / n;
+		let var = if values.len() > 1 {
+			values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / (n - 1.)
+		} else {
+			0.
+		};
+		Estimate { value: mean as f32, error: (var / n).sqrt() as f32 }
+	}
+}
+
+/// Van der Corput radical inverse of `i` in `base`
+pub fn radical_inverse(mut i: u32, base: u32) -> f64 {
+	let mut inv = 1. / base as f64;
+	let mut x = 0.;
+	while i > 0 {
+		x += (i % base) as f64 * inv;
+		i /= base;
+		inv /= base as f64;
+	}
+	x
+}
+
+/// The `i`-th point of the two-dimensional Sobol sequence.
+/// The first dimension is van der Corput in base 2, the second uses the polynomial x + 1.
+pub fn sobol(mut i: u32) -> (f64, f64) {
+	let (mut x, mut y) = (0u32, 0u32);
+	let (mut v, mut w) = (1u32 << 31, 1u32 << 31);
+	while i > 0 {
+		if i & 1 == 1 {
+			x ^= v;
+			y ^= w;
+		}
+		i >>= 1;
+		v >>= 1;
+		w ^= w >> 1;
+	}
+	let scale = (1u64 << 32) as f64;
+	(x as f64 / scale, y as f64 / scale)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::f32;
+	use models::adaptive::coverage_estimate;
+
+	#[test]
+	fn test_sequences() {
+		assert_eq!(radical_inverse(1, 2), 0.5);
+		assert_eq!(radical_inverse(3, 2), 0.75);
+		assert!((radical_inverse(5, 3) - 7. / 9.).abs() < 1e-12);
+
+		let first: Vec<(f64, f64)> = (0..4).map(sobol).collect();
+		assert_eq!(first, vec![(0., 0.), (0.5, 0.5), (0.25, 0.75), (0.75, 0.25)]);
+
+		// Each quarter of the unit square gets exactly 4 of the first 16 points
+		let mut quarters = [0; 4];
+		for i in 0..16 {
+			let (u, v) = sobol(i);
+			quarters[(u * 2.) as usize * 2 + (v * 2.) as usize] += 1;
+		}
+		assert_eq!(quarters, [4; 4]);
+	}
+
+	#[test]
+	fn test_estimators() {
+		let mut conf = Configuration::new();
+		conf.w = 100.; conf.h = 100.;
+		conf.n = 1;
+		conf.counts = vec![1];
+		conf.radius = vec![20.];
+		let state = vec![Point::wrap(50.)];
+		let exact = 400. * f32::consts::PI;
+
+		let mut errors = Vec::new();
+		for name in &["random", "halton", "sobol", "stratified"] {
+			let sampling = Sampling { sampler: Sampler::from_name(name).unwrap(), samples: 20000 };
+			let e = coverage_estimate(&conf, &state, &sampling);
+			println!("{}: {} +- {} (exact {})", name, e.value, e.error, exact);
+			assert!(e.error > 0.);
+			assert!((e.value - exact).abs() < 5. * e.error + 0.02 * exact);
+			errors.push(e.error);
+		}
+		assert!(errors[1] < errors[0] && errors[2] < errors[0] && errors[3] < errors[0]);
+	}
+}
@@ -0,0 +1,104 @@
+use super::*;
+
+/// Detection model of a sensor type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SensingModel {
+	/// Detects every point within the sensing radius
+	Binary,
+	/// Elfes' probabilistic model. Detection is certain up to `radius - re`,
+	/// decays as exp(-lambda * a^beta) up to `radius + re` and is zero beyond.
+	Elfes { re: f32, lambda: f32, beta: f32 }
+}
+
+impl SensingModel {
+	/// Probability of detecting a point at distance `d` from a sensor of the given radius
+	pub fn probability(&self, radius: f32, d: f32) -> f32 {
+		match *self {
+			SensingModel::Binary => {
+				if d <= radius { 1. } else { 0. }
+			},
+			SensingModel::Elfes { re, lambda, beta } => {
+				if d <= radius - re {
+					1.
+				} else if d >= radius + re {
+					0.
+				} else {
+					let a = d - (radius - re);
+					(-lambda * a.powf(beta)).exp()
+				}
+			}
+		}
+	}
+
+	/// Largest distance at which a detection is possible
+	pub fn reach(&self, radius: f32) -> f32 {
+		match *self {
+			SensingModel::Binary => radius,
+			SensingModel::Elfes { re, .. } => radius + re
+		}
+	}
+}
+
+/// Probability that at least one sensor detects `p`, i.e. 1 - ∏(1 - p_i)
+pub fn detection_probability(circles: &Vec<Circle>, p: &Point) -> f32 {
+	let mut miss = 1.;
+	for c in circles {
+		miss *= 1. - c.detection(p);
+		if miss == 0. {
+			break;
+		}
+	}
+
+	1. - miss
+}
+
+/// Like `detection_probability`, only considering the sensors listed in `candidates`
+pub fn detection_probability_among(circles: &[Circle], candidates: &[usize], p: &Point) -> f32 {
+	let mut miss = 1.;
+	for i in candidates {
+		miss *= 1. - circles[*i].detection(p);
+		if miss == 0. {
+			break;
+		}
+	}
+
+	1. - miss
+}
+
+/// Checks whether `p` is detected with at least the configured probability
+pub fn is_covered(conf: &Configuration, circles: &Vec<Circle>, p: &Point) -> bool {
+	detection_probability(circles, p) >= conf.threshold
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_elfes_profile() {
+		let m = SensingModel::Elfes { re: 2., lambda: 0.5, beta: 1. };
+		assert_eq!(m.probability(10., 7.), 1.);
+		assert_eq!(m.probability(10., 12.), 0.);
+		assert!((m.probability(10., 9.) - (-0.5f32).exp()).abs() < 1e-6);
+		assert!(m.probability(10., 9.) > m.probability(10., 11.));
+		assert_eq!(m.reach(10.), 12.);
+	}
+
+	#[test]
+	fn test_combined_probability() {
+		let m = SensingModel::Elfes { re: 2., lambda: 1., beta: 1. };
+		let circles = vec![
+			Circle{sensing: m, ..Circle::new(Point{x: 0., y: 0.}, 10.)},
+			Circle{sensing: m, ..Circle::new(Point{x: 18., y: 0.}, 10.)}
+		];
+		let p = Point{x: 9., y: 0.};
+		let single = m.probability(10., 9.);
+		let expected = 1. - (1. - single) * (1. - single);
+		assert!((detection_probability(&circles, &p) - expected).abs() < 1e-6);
+
+		let mut conf = Configuration::new();
+		conf.threshold = 0.5;
+		assert!(!is_covered(&conf, &circles[0..1].to_vec(), &p));
+		assert!(is_covered(&conf, &circles, &p));
+	}
+}
@@ -3,10 +3,12 @@
 from __future__ import print_function
 
 import sys
+import json
 try:
 	import matplotlib
 	matplotlib.use('Agg')
 	import matplotlib.pyplot as plt
+	import matplotlib.patches
 except ImportError:
 	print('Missing python modules. Run pip install -r requirements.txt to install them.')
 	exit(1)
@@ -22,16 +24,49 @@ def load_results(path):
 
 		# Group circles by their radius for coloring
 		circles = {}
+		sinks = []
+		fixed = []
 		rd = 0.
 		for l in lines[2:]:
-			x, y, r = l.strip().split(' ')
-			x, y, r = float(x), float(y), float(r)
+			if l.startswith('sink'):
+				_, x, y = l.strip().split(' ')
+				sinks.append((float(x), float(y)))
+				continue
+			if l.strip().endswith('fixed'):
+				x, y, r = [float(v) for v in l.strip().split(' ')[:3]]
+				fixed.append((x, y, r))
+				continue
+			tokens = l.strip().split(' ')
+			if 'type' in tokens:
+				tokens = tokens[:tokens.index('type')]
+			values = [float(v) for v in tokens]
+			x, y, r = values[:3]
+			# Sector sensors carry heading and field of view in degrees
+			sector = tuple(values[3:5]) if len(values) >= 5 else None
 			if r != rd:
 				rd = r
 				circles[rd] = []
-			circles[rd].append((x, y))
+			circles[rd].append((x, y, sector))
 		
-		return w, h, cov, circles
+		return w, h, cov, circles, sinks, fixed
+
+
+def load_routes(path):
+	"""Returns the routing tree edges of a JSON result as pairs of points."""
+	with open(path, 'rt') as f:
+		result = json.load(f)
+	sensors = [(s['x'], s['y']) for s in result['sensors']]
+	sinks = [(s['x'], s['y']) for s in result['sinks']]
+	edges = []
+	for r in result['routing']['routes']:
+		if r['hops'] is None:
+			continue
+		if r['parent'] is None:
+			target = sinks[r['sink']]
+		else:
+			target = sensors[r['parent']]
+		edges.append((sensors[r['sensor']], target))
+	return edges
 
 
 def draw_circle(center, radius, ax, **kwargs):
@@ -40,7 +75,13 @@ def draw_circle(center, radius, ax, **kwargs):
 	ax.plot(center[0], center[1], 'o', color=kwargs.get('color', 'black'))
 
 
-def draw_graph(w, h, cov, circles):
+def draw_sector(center, radius, heading, fov, ax, **kwargs):
+	wedge = matplotlib.patches.Wedge(center, radius, heading - fov / 2., heading + fov / 2., **kwargs)
+	ax.add_artist(wedge)
+	ax.plot(center[0], center[1], 'o', color=kwargs.get('color', 'black'))
+
+
+def draw_graph(w, h, cov, circles, sinks, fixed, edges):
 	fig = plt.figure()
 	ax = fig.add_subplot(111)
 
@@ -52,24 +93,38 @@ def draw_graph(w, h, cov, circles):
 
 	for i, r in enumerate(circles.keys()):
 		color = COLORS[i % len(COLORS)]
-		for c in circles[r]:
-			draw_circle(c, r, ax, alpha=0.5, color=color)
+		for x, y, sector in circles[r]:
+			if sector is None:
+				draw_circle((x, y), r, ax, alpha=0.5, color=color)
+			else:
+				draw_sector((x, y), r, sector[0], sector[1], ax, alpha=0.5, color=color)
+	# Pre-installed sensors are grey and hatched
+	for x, y, r in fixed:
+		draw_circle((x, y), r, ax, alpha=0.4, color='grey', hatch='//')
+	for p1, p2 in edges:
+		ax.plot([p1[0], p2[0]], [p1[1], p2[1]], color='black', linewidth=0.8)
+	for x, y in sinks:
+		ax.plot(x, y, 's', color='black', markersize=8)
 	ax.set_title('Coverage: %.2f' % cov, fontsize=10)
 	return fig, ax
 
 
 if __name__ == '__main__':
 	if len(sys.argv) < 3:
-		print('Usage: plot.py [INPUT_FILE] [OUTPUT_FILE]')
+		print('Usage: plot.py [INPUT_FILE] [OUTPUT_FILE] [--xkcd] [--json JSON_RESULT]')
 		exit(1)
 	fin = sys.argv[1]
 	fout = sys.argv[2]
-	if len(sys.argv) == 4 and sys.argv[3] == '--xkcd':
+	opts = sys.argv[3:]
+	if '--xkcd' in opts:
 		plt.xkcd()
+	edges = []
+	if '--json' in opts:
+		edges = load_routes(opts[opts.index('--json') + 1])
 	plot_name = fin.split('/')[-1].split('.')[0]
 
-	w, h, cov, circles = load_results(fin)
-	fig, ax = draw_graph(w, h, cov, circles)
+	w, h, cov, circles, sinks, fixed = load_results(fin)
+	fig, ax = draw_graph(w, h, cov, circles, sinks, fixed, edges)
 	ax.axis('off')
 	fig.suptitle(plot_name, fontsize=14)
 	fig.savefig(fout)
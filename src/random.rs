@@ -0,0 +1,89 @@
+//! Seedable random number generator shared by the GA and the models.
+//! Every thread owns one xorshift128+ generator. Its state can be read
+//! and restored, so checkpointed runs continue with the same random stream.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use rand::Rng;
+use time;
+
+static STREAMS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+	static STATE: Cell<[u64; 2]> = Cell::new(from_seed(
+		time::precise_time_ns() ^ ((STREAMS.fetch_add(1, Ordering::SeqCst) as u64) << 48)));
+}
+
+/// Expands a seed into a non-zero generator state with splitmix64
+pub fn from_seed(seed: u64) -> [u64; 2] {
+	let mut z = seed;
+	let mut next = || {
+		z = z.wrapping_add(0x9E3779B97F4A7C15);
+		let mut x = z;
+		x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+		x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+		x ^ (x >> 31)
+	};
+	let s = [next(), next()];
+	if s == [0, 0] { [1, 0] } else { s }
+}
+
+/// Reseeds the generator of the current thread
+pub fn seed(seed: u64) {
+	STATE.with(|s| s.set(from_seed(seed)));
+}
+
+/// State of the generator of the current thread
+pub fn state() -> [u64; 2] {
+	STATE.with(|s| s.get())
+}
+
+/// Continues the random stream from a saved state
+pub fn restore(state: [u64; 2]) {
+	STATE.with(|s| s.set(state));
+}
+
+/// Handle to the generator of the current thread
+pub struct ThreadRng;
+
+pub fn thread_rng() -> ThreadRng {
+	ThreadRng
+}
+
+impl Rng for ThreadRng {
+	fn next_u32(&mut self) -> u32 {
+		(self.next_u64() >> 32) as u32
+	}
+
+	fn next_u64(&mut self) -> u64 {
+		STATE.with(|cell| {
+			let s = cell.get();
+			let (mut s1, s0) = (s[0], s[1]);
+			let result = s0.wrapping_add(s1);
+			s1 ^= s1 << 23;
+			cell.set([s0, s1 ^ s0 ^ (s1 >> 17) ^ (s0 >> 26)]);
+			result
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_restore() {
+		seed(42);
+		let mut r = thread_rng();
+		let first: Vec<u64> = (0..5).map(|_| r.next_u64()).collect();
+
+		seed(42);
+		assert_eq!(r.next_u64(), first[0]);
+		let saved = state();
+		let rest: Vec<u64> = (0..4).map(|_| r.next_u64()).collect();
+		assert_eq!(rest, first[1..].to_vec());
+
+		restore(saved);
+		assert_eq!(r.next_u64(), first[1]);
+	}
+}